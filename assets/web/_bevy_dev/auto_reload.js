@@ -1,9 +1,11 @@
-// Automatically reload when the server restarts
+// Automatically reload when the server restarts or the app has been rebuilt
+// and forward the console output to the terminal
 // Adopted from <https://github.com/trunk-rs/trunk/pull/231>
 (function () {
   const baseUrl = window.location.host + '/_bevy_dev/websocket';
-  const websocketUrl = 'ws://' + baseUrl;
-  const httpUrl = 'http://' + baseUrl;
+  const isSecure = window.location.protocol === 'https:';
+  const websocketUrl = (isSecure ? 'wss://' : 'ws://') + baseUrl;
+  const httpUrl = (isSecure ? 'https://' : 'http://') + baseUrl;
 
   const pollIntervalMs = 5_000;
 
@@ -13,6 +15,85 @@
   let isFirstLoad = true;
   let isConnected = false;
 
+  /** Log messages that couldn't be sent yet, because the websocket isn't connected. */
+  const pendingLogs = [];
+  const maxPendingLogs = 1000;
+
+  function stringify(value) {
+    if (typeof value === "string") {
+      return value;
+    }
+    if (value instanceof Error) {
+      return value.stack || String(value);
+    }
+    try {
+      return JSON.stringify(value) ?? String(value);
+    } catch {
+      return String(value);
+    }
+  }
+
+  /** Format the arguments of a `console` call, resolving format specifiers like `%s` and `%c`. */
+  function formatArgs(args) {
+    args = Array.from(args);
+    let message = "";
+
+    if (typeof args[0] === "string") {
+      const format = args.shift();
+      message = format.replace(/%[sdifoOc%]/g, (specifier) => {
+        if (specifier === "%%") {
+          return "%";
+        }
+        if (args.length === 0) {
+          return specifier;
+        }
+        const arg = args.shift();
+        // Styles can't be displayed in the terminal
+        return specifier === "%c" ? "" : stringify(arg);
+      });
+    }
+
+    return [message, ...args.map(stringify)].filter((part) => part !== "").join(" ");
+  }
+
+  function sendLog(level, message) {
+    const msg = JSON.stringify({ type: "log", level, message });
+
+    if (isConnected && webSocket && webSocket.readyState === WebSocket.OPEN) {
+      webSocket.send(msg);
+    } else if (pendingLogs.length < maxPendingLogs) {
+      pendingLogs.push(msg);
+    }
+  }
+
+  function flushPendingLogs() {
+    while (pendingLogs.length > 0) {
+      webSocket.send(pendingLogs.shift());
+    }
+  }
+
+  // Forward the console output to the dev server
+  for (const level of ["error", "warn", "info", "log", "debug", "trace"]) {
+    const original = console[level].bind(console);
+
+    console[level] = (...args) => {
+      original(...args);
+      try {
+        sendLog(level, formatArgs(args));
+      } catch {
+        // Never break the app because of the forwarding
+      }
+    };
+  }
+
+  // Forward uncaught errors, e.g. Wasm panics
+  window.addEventListener("error", (event) => {
+    sendLog("error", event.error ? stringify(event.error) : event.message);
+  });
+  window.addEventListener("unhandledrejection", (event) => {
+    sendLog("error", "Unhandled promise rejection: " + stringify(event.reason));
+  });
+
   function onOpen() {
     if (isFirstLoad) {
       console.info("Connected to dev websocket");
@@ -23,6 +104,8 @@
 
     isFirstLoad = false;
     isConnected = true;
+
+    flushPendingLogs();
   }
 
   function onClose() {
@@ -36,6 +119,43 @@
     window.setTimeout(recreateWebsocket, pollIntervalMs);
   }
 
+  /** @type HTMLElement | undefined */
+  let errorOverlay;
+
+  function showBuildError(message) {
+    if (!errorOverlay) {
+      errorOverlay = document.createElement("div");
+      errorOverlay.style.cssText = [
+        "position: fixed",
+        "inset: 0",
+        "z-index: 2147483647",
+        "overflow: auto",
+        "padding: 2em",
+        "background: rgba(20, 20, 20, 0.92)",
+        "color: #ff6b6b",
+        "font: 14px/1.5 monospace",
+      ].join(";");
+      errorOverlay.addEventListener("click", () => {
+        errorOverlay.remove();
+        errorOverlay = undefined;
+      });
+      document.body.appendChild(errorOverlay);
+    }
+
+    errorOverlay.innerHTML = "";
+
+    const title = document.createElement("h2");
+    title.textContent = "Failed to build the app";
+    const details = document.createElement("pre");
+    details.style.whiteSpace = "pre-wrap";
+    details.textContent = message + "\n\nSee the terminal for the full compiler output.";
+    const hint = document.createElement("p");
+    hint.style.color = "#aaa";
+    hint.textContent = "The page will reload once the app builds again. Click to dismiss.";
+
+    errorOverlay.append(title, details, hint);
+  }
+
   function onMessage(ev) {
     try {
       const msg = JSON.parse(ev.data);
@@ -43,6 +163,23 @@
       switch (msg.type) {
         case "reload":
           window.location.reload();
+          break;
+        case "build-error":
+          console.error("Failed to build the app", msg.message);
+          showBuildError(msg.message);
+          break;
+        case "asset-changed": {
+          // Let the app re-fetch the asset, e.g. via Bevy's web asset reader
+          // The detail contains the `path` relative to the assets folder,
+          // the `modified` time and the SHA-256 `hash` of the new content
+          console.info("Asset changed:", msg.path);
+          window.dispatchEvent(
+            new CustomEvent("bevy-asset-changed", {
+              detail: { path: msg.path, modified: msg.modified, hash: msg.hash },
+            }),
+          );
+          break;
+        }
         default:
           console.warn("Unknown websocket message", msg);
       }
@@ -0,0 +1,427 @@
+//! Packing bundles into archives that can be uploaded to hosts like itch.io.
+//!
+//! The archives are deterministic: the files are sorted by path, all timestamps are fixed and
+//! permissions are normalized to `rw-r--r--` or `rwxr-xr-x` for executables, so the same bundle
+//! always results in the same archive.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use clap::ValueEnum;
+use flate2::{Compression, GzBuilder};
+use tar::{EntryType, Header};
+use zip::{CompressionMethod, DateTime, ZipWriter, write::SimpleFileOptions};
+
+/// The supported archive formats.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// A zip archive, as expected by most hosting sites.
+    Zip,
+    /// A gzip-compressed tarball.
+    #[value(name = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// The file extension of the archive, without leading dot.
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::TarGz => "tar.gz",
+        }
+    }
+}
+
+/// A file or directory to include in the archive.
+struct ArchiveEntry {
+    /// The path inside of the archive, separated by `/`.
+    name: String,
+    /// The path of the file on disk.
+    path: PathBuf,
+    /// The type of the entry, which determines its permissions.
+    kind: EntryKind,
+}
+
+/// The type of an archive entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    /// A regular file.
+    File,
+    /// A file that can be executed, e.g. the binary of a native app.
+    Executable,
+    /// A directory, only included in package tarballs.
+    Directory,
+}
+
+impl EntryKind {
+    /// The normalized Unix permissions of the entry.
+    fn mode(self) -> u32 {
+        match self {
+            Self::File => 0o644,
+            Self::Executable | Self::Directory => 0o755,
+        }
+    }
+}
+
+/// The default path of the archive for the given directory, e.g. `bundle` -> `bundle.zip`.
+pub(crate) fn default_archive_path(directory: &Path, format: ArchiveFormat) -> PathBuf {
+    let mut file_name = directory.file_name().unwrap_or_default().to_owned();
+    file_name.push(".");
+    file_name.push(format.extension());
+    directory.with_file_name(file_name)
+}
+
+/// Pack the contents of the directory into an archive at the given destination.
+///
+/// The files are placed at the root of the archive, without the directory itself.
+pub(crate) fn create_archive(
+    directory: &Path,
+    destination: &Path,
+    format: ArchiveFormat,
+) -> anyhow::Result<()> {
+    let mut entries = Vec::new();
+    collect_entries(directory, directory, false, &mut entries)
+        .with_context(|| format!("failed to read {}", directory.display()))?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).context("failed to create archive directory")?;
+    }
+    let writer = BufWriter::new(
+        File::create(destination)
+            .with_context(|| format!("failed to create {}", destination.display()))?,
+    );
+
+    let mut writer = match format {
+        ArchiveFormat::Zip => write_zip(writer, &entries)?,
+        ArchiveFormat::TarGz => {
+            // The modification time and OS are fixed to keep the archive deterministic
+            let encoder = GzBuilder::new()
+                .mtime(0)
+                .operating_system(255)
+                .write(writer, Compression::best());
+            write_tar(encoder, &entries)?.finish()?
+        }
+    };
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Pack the contents of the directory into a gzip-compressed tarball, as used in Debian packages.
+///
+/// In contrast to [`create_archive`], the directories get their own entries, starting with the
+/// root directory `./`, and all paths start with `./`, like in the tarballs created by `dpkg-deb`.
+pub(crate) fn write_package_tarball<W: Write>(directory: &Path, writer: W) -> anyhow::Result<W> {
+    let mut entries = Vec::new();
+    collect_entries(directory, directory, true, &mut entries)
+        .with_context(|| format!("failed to read {}", directory.display()))?;
+    for entry in &mut entries {
+        entry.name.insert_str(0, "./");
+    }
+    entries.push(ArchiveEntry {
+        name: "./".to_owned(),
+        path: directory.to_path_buf(),
+        kind: EntryKind::Directory,
+    });
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let encoder = GzBuilder::new()
+        .mtime(0)
+        .operating_system(255)
+        .write(writer, Compression::best());
+
+    Ok(write_tar(encoder, &entries)?.finish()?)
+}
+
+/// Recursively collect all files in the directory.
+///
+/// Directories only get their own entries if `include_directories` is `true`.
+fn collect_entries(
+    root: &Path,
+    directory: &Path,
+    include_directories: bool,
+    entries: &mut Vec<ArchiveEntry>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        let name = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if path.is_dir() {
+            if include_directories {
+                entries.push(ArchiveEntry {
+                    name: format!("{name}/"),
+                    path: path.clone(),
+                    kind: EntryKind::Directory,
+                });
+            }
+            collect_entries(root, &path, include_directories, entries)?;
+        } else {
+            let kind = if is_executable(&path)? {
+                EntryKind::Executable
+            } else {
+                EntryKind::File
+            };
+            entries.push(ArchiveEntry { name, path, kind });
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the file has any executable permission bit set.
+///
+/// Windows doesn't have executable permissions, so no file is considered executable there.
+fn is_executable(path: &Path) -> io::Result<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        Ok(fs::metadata(path)?.permissions().mode() & 0o111 != 0)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(false)
+    }
+}
+
+/// Write the entries as zip archive.
+fn write_zip<W: Write + Seek>(writer: W, entries: &[ArchiveEntry]) -> anyhow::Result<W> {
+    let mut zip = ZipWriter::new(writer);
+
+    for entry in entries {
+        let mut file =
+            File::open(&entry.path).with_context(|| format!("failed to read {}", entry.name))?;
+        let size = file.metadata()?.len();
+
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(Some(9))
+            .last_modified_time(DateTime::default())
+            .unix_permissions(entry.kind.mode())
+            .large_file(size >= u64::from(u32::MAX));
+
+        zip.start_file(entry.name.as_str(), options)
+            .with_context(|| format!("failed to add {} to the archive", entry.name))?;
+        io::copy(&mut file, &mut zip)
+            .with_context(|| format!("failed to add {} to the archive", entry.name))?;
+    }
+
+    Ok(zip.finish()?)
+}
+
+/// Write the entries as tar archive.
+fn write_tar<W: Write>(writer: W, entries: &[ArchiveEntry]) -> anyhow::Result<W> {
+    let mut tar = tar::Builder::new(writer);
+
+    for entry in entries {
+        let mut header = Header::new_gnu();
+        header.set_mode(entry.kind.mode());
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mtime(0);
+
+        let result = if entry.kind == EntryKind::Directory {
+            header.set_entry_type(EntryType::Directory);
+            header.set_size(0);
+            append_tar_entry(&mut tar, &mut header, &entry.name, io::empty())
+        } else {
+            let file = File::open(&entry.path)
+                .with_context(|| format!("failed to read {}", entry.name))?;
+            header.set_entry_type(EntryType::Regular);
+            header.set_size(file.metadata()?.len());
+            append_tar_entry(&mut tar, &mut header, &entry.name, file)
+        };
+        result.with_context(|| format!("failed to add {} to the archive", entry.name))?;
+    }
+
+    Ok(tar.into_inner()?)
+}
+
+/// Append an entry to the tarball, keeping its name exactly as given.
+///
+/// [`tar::Builder::append_data`] normalizes the name, e.g. it strips a leading `./`.
+/// Names that don't fit into the header are preceded by a GNU long name entry, like GNU tar does.
+fn append_tar_entry<W: Write>(
+    tar: &mut tar::Builder<W>,
+    header: &mut Header,
+    name: &str,
+    data: impl Read,
+) -> io::Result<()> {
+    let name_field = &mut header.as_old_mut().name;
+
+    if name.len() > name_field.len() {
+        let mut long_name = Header::new_gnu();
+        long_name.as_old_mut().name[..13].copy_from_slice(b"././@LongLink");
+        long_name.set_mode(0o644);
+        long_name.set_uid(0);
+        long_name.set_gid(0);
+        long_name.set_mtime(0);
+        long_name.set_entry_type(EntryType::GNULongName);
+        // The name is null-terminated
+        long_name.set_size(name.len() as u64 + 1);
+        long_name.set_cksum();
+        tar.append(&long_name, name.as_bytes().chain(&[0][..]))?;
+    }
+
+    let length = name.len().min(name_field.len());
+    name_field[..length].copy_from_slice(&name.as_bytes()[..length]);
+    header.set_cksum();
+    tar.append(header, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::read::GzDecoder;
+    use zip::ZipArchive;
+
+    use super::*;
+
+    fn test_bundle() -> anyhow::Result<tempfile::TempDir> {
+        let bundle = tempfile::tempdir()?;
+        fs::create_dir(bundle.path().join("build"))?;
+        fs::write(bundle.path().join("index.html"), "<html></html>")?;
+        fs::write(bundle.path().join("build").join("app.js"), "a".repeat(1000))?;
+        Ok(bundle)
+    }
+
+    /// The path, mode and size of every entry of the tarball.
+    fn tar_entries(path: &Path) -> anyhow::Result<Vec<(String, u32, u64)>> {
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+        let mut entries = Vec::new();
+
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let header = entry.header();
+            entries.push((
+                String::from_utf8_lossy(&entry.path_bytes()).into_owned(),
+                header.mode()?,
+                header.size()?,
+            ));
+        }
+
+        Ok(entries)
+    }
+
+    #[test]
+    fn should_create_identical_zip_archives() -> anyhow::Result<()> {
+        let bundle = test_bundle()?;
+        let output = tempfile::tempdir()?;
+        let first = output.path().join("first.zip");
+        let second = output.path().join("second.zip");
+
+        create_archive(bundle.path(), &first, ArchiveFormat::Zip)?;
+        // Modification times don't affect the archive
+        fs::write(bundle.path().join("index.html"), "<html></html>")?;
+        create_archive(bundle.path(), &second, ArchiveFormat::Zip)?;
+
+        assert_eq!(fs::read(&first)?, fs::read(&second)?);
+
+        // The files are sorted by path
+        let mut archive = ZipArchive::new(File::open(&first)?)?;
+        let names = (0..archive.len())
+            .map(|index| Ok(archive.by_index(index)?.name().to_owned()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(names, ["build/app.js", "index.html"]);
+        let mut content = String::new();
+        io::Read::read_to_string(&mut archive.by_name("build/app.js")?, &mut content)?;
+        assert_eq!(content, "a".repeat(1000));
+        Ok(())
+    }
+
+    #[test]
+    fn should_create_tar_gz_archive() -> anyhow::Result<()> {
+        let bundle = test_bundle()?;
+        let output = tempfile::tempdir()?;
+        let path = default_archive_path(&output.path().join("app"), ArchiveFormat::TarGz);
+
+        create_archive(bundle.path(), &path, ArchiveFormat::TarGz)?;
+
+        assert!(path.ends_with("app.tar.gz"));
+        assert_eq!(
+            tar_entries(&path)?,
+            [
+                ("build/app.js".to_owned(), 0o644, 1000),
+                ("index.html".to_owned(), 0o644, 13),
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_keep_executable_permissions() -> anyhow::Result<()> {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let bundle = test_bundle()?;
+        let executable = bundle.path().join("app");
+        fs::write(&executable, "")?;
+        fs::set_permissions(&executable, fs::Permissions::from_mode(0o700))?;
+
+        let output = tempfile::tempdir()?;
+        let tar_path = output.path().join("bundle.tar.gz");
+        create_archive(bundle.path(), &tar_path, ArchiveFormat::TarGz)?;
+
+        // The executable is sorted first, the other files keep the default permissions
+        let entries = tar_entries(&tar_path)?;
+        assert_eq!(entries[0], ("app".to_owned(), 0o755, 0));
+        assert_eq!(entries[1].1, 0o644);
+
+        let zip_path = output.path().join("bundle.zip");
+        create_archive(bundle.path(), &zip_path, ArchiveFormat::Zip)?;
+        let mut archive = ZipArchive::new(File::open(&zip_path)?)?;
+        assert_eq!(archive.by_name("app")?.unix_mode(), Some(0o100755));
+        assert_eq!(archive.by_name("index.html")?.unix_mode(), Some(0o100644));
+        Ok(())
+    }
+
+    #[test]
+    fn should_support_long_paths() -> anyhow::Result<()> {
+        let bundle = tempfile::tempdir()?;
+        let directory = bundle.path().join("a".repeat(120));
+        fs::create_dir(&directory)?;
+        fs::write(directory.join("b".repeat(90)), "")?;
+
+        let output = tempfile::tempdir()?;
+        let path = output.path().join("bundle.tar.gz");
+        create_archive(bundle.path(), &path, ArchiveFormat::TarGz)?;
+
+        assert_eq!(
+            tar_entries(&path)?[0].0,
+            format!("{}/{}", "a".repeat(120), "b".repeat(90))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_include_directories_in_package_tarballs() -> anyhow::Result<()> {
+        let bundle = test_bundle()?;
+        let output = tempfile::tempdir()?;
+        let path = output.path().join("data.tar.gz");
+        fs::write(&path, write_package_tarball(bundle.path(), Vec::new())?)?;
+
+        let entries = tar_entries(&path)?;
+        assert_eq!(
+            entries
+                .iter()
+                .map(|(name, ..)| name.as_str())
+                .collect::<Vec<_>>(),
+            ["./", "./build/", "./build/app.js", "./index.html"]
+        );
+        assert_eq!(entries[1].1, 0o755);
+        Ok(())
+    }
+}
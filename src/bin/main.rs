@@ -6,6 +6,7 @@ use bevy_cli::{
     commands::{
         build::{BuildArgs, build},
         completions::completions,
+        config::{ConfigArgs, config},
         lint::{LintArgs, lint},
         new::{NewArgs, new},
         run::{RunArgs, run},
@@ -49,6 +50,7 @@ fn main() -> ExitCode {
         Subcommands::Lint(mut args) => lint(&mut args),
         Subcommands::Build(mut args) => build(&mut args),
         Subcommands::Run(mut args) => run(&mut args),
+        Subcommands::Config(args) => config(&args),
         Subcommands::Completions { shell } => {
             completions::<Cli>(shell);
             Ok(())
@@ -124,6 +126,9 @@ pub enum Subcommands {
     /// To see the full list of options, run `bevy lint -- --help`.
     #[command(after_help = lint_after_help())]
     Lint(LintArgs),
+    /// Inspect the config of your Bevy app.
+    #[command(after_help = config_after_help())]
+    Config(ConfigArgs),
     /// Prints the auto-completion script for a specific shell.
     ///
     /// The result of this command is intended to be passed to the `source` command, such as
@@ -152,6 +157,26 @@ fn run_after_help() -> String {
     message
 }
 
+fn config_after_help() -> String {
+    let mut message = String::new();
+
+    let header = style::HEADER;
+    let literal = style::LITERAL;
+
+    _ = writeln!(message, "{header}Examples:{header:#}");
+    _ = writeln!(message, "  {literal}bevy config show{literal:#}");
+    _ = writeln!(
+        message,
+        "  {literal}bevy config show --web --release --json{literal:#}"
+    );
+    _ = writeln!(
+        message,
+        "  {literal}bevy config schema > bevy_cli.schema.json{literal:#}"
+    );
+
+    message
+}
+
 fn lint_after_help() -> String {
     let mut message = String::new();
 
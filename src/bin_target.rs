@@ -1,6 +1,13 @@
-use std::path::PathBuf;
+use std::{
+    env::consts::EXE_SUFFIX,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use cargo_metadata::{Metadata, Package, TargetKind};
+use tracing::info;
+
+use crate::dynamic_linking::set_library_path;
 
 #[derive(Debug, Clone)]
 pub struct BinTarget<'p> {
@@ -12,6 +19,61 @@ pub struct BinTarget<'p> {
     pub bin_name: String,
 }
 
+impl BinTarget<'_> {
+    /// The Bevy assets folder of the app, if it exists.
+    ///
+    /// The `assets` folder of the package takes precedence over the one in the workspace.
+    pub(crate) fn assets_path(&self) -> Option<PathBuf> {
+        let assets_path = Path::new("assets");
+
+        let package_assets = self
+            .package
+            .manifest_path
+            .parent()
+            .map(|package_root| package_root.as_std_path().join(assets_path));
+
+        if let Some(package_assets) = package_assets.filter(|path| path.exists()) {
+            info!("using package assets.");
+            Some(package_assets)
+        } else if assets_path.exists() {
+            info!("using workspace assets.");
+            Some(assets_path.into())
+        } else {
+            None
+        }
+    }
+
+    /// The path to the compiled binary.
+    pub(crate) fn executable_path(&self) -> PathBuf {
+        self.artifact_directory
+            .join(format!("{}{EXE_SUFFIX}", self.bin_name))
+    }
+
+    /// Create a command to launch the compiled binary.
+    ///
+    /// Like `cargo run`, the manifest directory is passed on, which Bevy uses to locate the assets,
+    /// and the library search path includes the dynamically linked dependencies.
+    pub(crate) fn command(&self) -> anyhow::Result<Command> {
+        let mut command = Command::new(self.executable_path());
+
+        // Examples are placed in a subfolder of the profile directory
+        let profile_directory = if self.artifact_directory.ends_with("examples") {
+            self.artifact_directory
+                .parent()
+                .unwrap_or(&self.artifact_directory)
+        } else {
+            &self.artifact_directory
+        };
+        set_library_path(&mut command, profile_directory)?;
+
+        if let Some(package_root) = self.package.manifest_path.parent() {
+            command.env("CARGO_MANIFEST_DIR", package_root);
+        }
+
+        Ok(command)
+    }
+}
+
 /// Determine which binary target should be run.
 ///
 /// The `--package` arg narrows down the search space to the given package,
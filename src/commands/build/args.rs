@@ -1,20 +1,23 @@
-#[cfg(feature = "web")]
+#[cfg(any(feature = "web", feature = "archive"))]
 use std::path::PathBuf;
 
-#[cfg(feature = "web")]
-use clap::ArgAction;
-use clap::{Args, Subcommand};
+use clap::{ArgAction, Args, Subcommand};
 
 #[cfg(feature = "web")]
 use crate::external_cli::external_cli_args::ExternalCliArgs;
 #[cfg(all(feature = "unstable", feature = "web"))]
 use crate::web::unstable::UnstableWebArgs;
+#[cfg(feature = "web")]
+use crate::web::{cache_busting::FileHashing, compress::CompressionFormat};
+#[cfg(feature = "archive")]
+use crate::{archive::ArchiveFormat, native::package::PackageFormat};
 use crate::{
     config::CliConfig,
     external_cli::{
         arg_builder::ArgBuilder,
         cargo::{build::CargoBuildArgs, install::AutoInstall},
     },
+    profiles::NATIVE_DIST_PROFILE,
 };
 
 /// Arguments for building a Bevy project.
@@ -28,6 +31,37 @@ pub struct BuildArgs {
     #[arg(long = "yes", default_value_t = false)]
     pub confirm_prompts: bool,
 
+    /// Bundle the executable, the assets and additional files into a single folder.
+    ///
+    /// The additional files can be configured with the `bundle-files` config key.
+    #[arg(short = 'b', long = "bundle", action = ArgAction::SetTrue, default_value_t = false)]
+    pub create_bundle: bool,
+
+    /// Pack the bundle into an archive, e.g. to hand it to testers.
+    #[cfg(feature = "archive")]
+    #[arg(long = "archive", value_name = "FORMAT", requires = "create_bundle")]
+    pub archive: Option<ArchiveFormat>,
+
+    /// The path of the archive.
+    ///
+    /// Defaults to the bundle path with the extension of the archive format.
+    #[cfg(feature = "archive")]
+    #[arg(long = "archive-path", value_name = "PATH", requires = "archive")]
+    pub archive_path: Option<PathBuf>,
+
+    /// Package the bundle for Linux, as a Debian package or an AppImage-style `AppDir`.
+    ///
+    /// The package uses the metadata of the Cargo package and the `app` config table.
+    #[cfg(feature = "archive")]
+    #[arg(long = "package-format", value_name = "FORMAT")]
+    pub package_format: Option<PackageFormat>,
+
+    /// Apply a preset from the config on top of the other config, e.g. `--preset demo`.
+    ///
+    /// Presets are defined in the `preset.<NAME>` tables of the config.
+    #[arg(long = "preset", value_name = "NAME")]
+    pub preset: Option<String>,
+
     /// Arguments to forward to `cargo build`.
     #[clap(flatten)]
     pub cargo_args: CargoBuildArgs,
@@ -59,15 +93,24 @@ impl BuildArgs {
     pub(crate) fn is_release(&self) -> bool {
         self.cargo_args.compilation_args.profile.as_deref() == Some("release")
             || self.cargo_args.compilation_args.profile.as_deref() == Some("web-release")
+            || self.cargo_args.compilation_args.profile.as_deref() == Some(NATIVE_DIST_PROFILE)
             || self.cargo_args.compilation_args.is_release
     }
 
     /// The profile used to compile the app.
-    #[cfg(feature = "web")]
     pub(crate) fn profile(&self) -> &str {
         self.cargo_args.compilation_args.profile(self.is_web())
     }
 
+    /// Whether the app is built natively with the `dev` profile.
+    pub(crate) fn is_native_dev(&self) -> bool {
+        !self.is_web()
+            && self.profile() == "dev"
+            && !self
+                .target()
+                .is_some_and(|target| target.starts_with("wasm"))
+    }
+
     /// The targeted platform.
     pub(crate) fn target(&self) -> Option<String> {
         self.cargo_args.compilation_args.target(self.is_web())
@@ -168,7 +211,66 @@ pub struct BuildWebArgs {
     #[arg(long = "bundle-dir", requires = "create_packed_bundle")]
     pub bundle_dir: Option<PathBuf>,
 
+    /// Create pre-compressed copies of the bundled files, e.g. `--compress gzip,brotli`.
+    ///
+    /// Web servers can serve them directly to browsers that support the compression.
+    #[arg(
+        long = "compress",
+        value_name = "FORMAT",
+        value_delimiter = ',',
+        requires = "create_packed_bundle"
+    )]
+    pub compress: Vec<CompressionFormat>,
+
+    /// Add content hashes to the file names of the Wasm binary and JS bindings.
+    ///
+    /// The `index.html` is updated to reference the renamed files with integrity attributes and
+    /// a `manifest.json` maps the original file names to the hashed ones.
+    #[arg(long = "hash-filenames", action = ArgAction::SetTrue, default_value_t = false, requires = "create_packed_bundle")]
+    pub hash_filenames: bool,
+
+    /// Also add copies of the assets with content hashes in their file names.
+    ///
+    /// The original assets are kept, because Bevy loads them by their path. To use the hashed
+    /// copies, the app needs to resolve them via the `manifest.json`.
+    #[arg(long = "hash-assets", action = ArgAction::SetTrue, default_value_t = false, requires = "hash_filenames")]
+    pub hash_assets: bool,
+
+    /// Print a breakdown of the Wasm binary size by sections, crates and functions.
+    ///
+    /// The report is compared to the one of the previous build with `--size-report`.
+    #[arg(long = "size-report", action = ArgAction::SetTrue, default_value_t = false)]
+    pub size_report: bool,
+
+    /// Pack the bundle into an archive, e.g. to upload it to itch.io.
+    #[cfg(feature = "archive")]
+    #[arg(
+        long = "archive",
+        value_name = "FORMAT",
+        requires = "create_packed_bundle"
+    )]
+    pub archive: Option<ArchiveFormat>,
+
+    /// The path of the archive.
+    ///
+    /// Defaults to the bundle path with the extension of the archive format.
+    #[cfg(feature = "archive")]
+    #[arg(long = "archive-path", value_name = "PATH", requires = "archive")]
+    pub archive_path: Option<PathBuf>,
+
     #[cfg(feature = "unstable")]
     #[clap(flatten)]
     pub unstable: UnstableWebArgs,
 }
+
+#[cfg(feature = "web")]
+impl BuildWebArgs {
+    /// Which files of the packed bundle get content hashes in their names.
+    pub(crate) fn file_hashing(&self) -> FileHashing {
+        match (self.hash_filenames, self.hash_assets) {
+            (true, true) => FileHashing::All,
+            (true, false) => FileHashing::BuildArtifacts,
+            (false, _) => FileHashing::Disabled,
+        }
+    }
+}
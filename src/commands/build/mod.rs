@@ -1,12 +1,22 @@
 //! Provides functionalities to build a Bevy app targeting either native or web platforms.
 
+use anyhow::Context as _;
 pub use args::*;
-use cargo_metadata::TargetKind;
-use tracing::info;
+use cargo_metadata::{Metadata, TargetKind};
+use tracing::{info, warn};
 
 #[cfg(feature = "web")]
 use crate::web::build::build_web;
-use crate::{commands::get_package, config::CliConfig, external_cli::cargo};
+#[cfg(feature = "archive")]
+use crate::{
+    archive::{create_archive, default_archive_path},
+    native::package::create_package,
+};
+use crate::{
+    bin_target::select_run_binary, commands::get_package, config::CliConfig,
+    dynamic_linking::bevy_dynamic_linking_feature, external_cli::cargo,
+    native::bundle::create_native_bundle, profiles::configure_default_native_profiles,
+};
 
 mod args;
 
@@ -25,23 +35,37 @@ pub fn build(args: &mut BuildArgs) -> anyhow::Result<()> {
         false,
     )?;
 
-    // apply the package specific config, otherwise use the default config (this happens when
-    // `bevy build` was called from a workspace root with no package selection (we do not support
-    // workspace config at the moment).
-    let mut config = if let Some(package) = package {
-        CliConfig::for_package(&metadata, package, args.is_web(), args.is_release())?
-    } else {
-        CliConfig::default()
-    };
+    // Apply the workspace config, overwritten by the package specific config. If no package is
+    // selected, e.g. in the root of a virtual workspace, only the workspace config is used.
+    let mut config = CliConfig::resolve(
+        &metadata,
+        package,
+        args.is_web(),
+        args.is_release(),
+        args.preset.as_deref(),
+        args.cargo_args.compilation_args.target.as_deref(),
+    )?;
 
     // Read config files hierarchically from the current directory, merge them,
     // apply environment variables, and resolve relative paths.
     let cargo_config = cargo_config2::Config::load()?;
 
     config.append_cargo_config_rustflags(args.target(), &cargo_config)?;
+    config.append_linker_rustflags(args.target(), &cargo_config)?;
 
     args.apply_config(&config);
 
+    // Dynamic linking only speeds up iteration, it must not leak into release or web builds
+    if config.dynamic_linking()
+        && args.is_native_dev()
+        && let Some(package) = package
+        && let Some(feature) = bevy_dynamic_linking_feature(&metadata, package)
+        && !args.cargo_args.feature_args.features.contains(&feature)
+    {
+        info!("enabling {feature}");
+        args.cargo_args.feature_args.features.push(feature);
+    }
+
     // If a specific example was passed, extend the already present features with the
     // required_features from this example.
     if let Some(example) = &args.cargo_args.target_args.example
@@ -68,15 +92,108 @@ pub fn build(args: &mut BuildArgs) -> anyhow::Result<()> {
 
     #[cfg(feature = "web")]
     if args.is_web() {
+        // `bevy build --bundle web` is treated like `bevy build web --bundle`
+        if let Some(BuildSubcommands::Web(web_args)) = &mut args.subcommand {
+            web_args.create_packed_bundle |= args.create_bundle;
+
+            #[cfg(feature = "archive")]
+            if web_args.archive.is_none() {
+                web_args.archive = args.archive;
+                web_args.archive_path = args.archive_path.clone();
+            }
+        }
+
         build_web(args, &metadata)?;
         return Ok(());
     }
 
+    let mut profile_args = configure_default_native_profiles(&metadata)?;
+    // `--config` args are resolved from left to right,
+    // so the default configuration needs to come before the user args
+    profile_args.append(&mut args.cargo_args.common_args.config);
+    args.cargo_args.common_args.config = profile_args;
+
     let cargo_args = args.cargo_args_builder();
     cargo::build::command()
         .args(cargo_args)
         .env("RUSTFLAGS", args.rustflags())
         .ensure_status(args.auto_install())?;
 
+    #[cfg(feature = "archive")]
+    let create_bundle = args.create_bundle || args.package_format.is_some();
+    #[cfg(not(feature = "archive"))]
+    let create_bundle = args.create_bundle;
+
+    if create_bundle {
+        bundle(args, &metadata, &config, &cargo_config)?;
+    }
+
+    Ok(())
+}
+
+/// Bundle the compiled native app into a folder and optionally pack it into an archive or package.
+fn bundle(
+    args: &BuildArgs,
+    metadata: &Metadata,
+    config: &CliConfig,
+    #[cfg_attr(not(feature = "archive"), expect(unused_variables))]
+    cargo_config: &cargo_config2::Config,
+) -> anyhow::Result<()> {
+    let bin_target = select_run_binary(
+        metadata,
+        args.cargo_args.package_args.package.as_deref(),
+        args.cargo_args.target_args.bin.as_deref(),
+        args.cargo_args.target_args.example.as_deref(),
+        args.target().as_deref(),
+        args.profile(),
+    )?;
+
+    if args
+        .cargo_args
+        .feature_args
+        .features
+        .iter()
+        .any(|feature| feature.ends_with("/dynamic_linking"))
+    {
+        warn!(
+            "the bundle links Bevy dynamically and won't start on other machines, build with `--release` to distribute it"
+        );
+    }
+
+    let bundle = create_native_bundle(
+        metadata,
+        args.profile(),
+        args.target().as_deref(),
+        &bin_target,
+        config.bundle_files(),
+    )
+    .context("failed to create native bundle")?;
+    info!("created bundle at file://{}", bundle.path.display());
+
+    #[cfg(feature = "archive")]
+    if let Some(format) = args.archive {
+        let archive_path = args
+            .archive_path
+            .clone()
+            .unwrap_or_else(|| default_archive_path(&bundle.path, format));
+        create_archive(&bundle.path, &archive_path, format)
+            .context("failed to create archive of the bundle")?;
+        info!("created archive at file://{}", archive_path.display());
+    }
+
+    #[cfg(feature = "archive")]
+    if let Some(format) = args.package_format {
+        if !args.is_release() {
+            warn!("packaging a debug build, build with `--release` to distribute the package");
+        }
+
+        let target = match args.target() {
+            Some(target) => target,
+            None => cargo_config.host_triple()?.to_owned(),
+        };
+        let package_path = create_package(&bundle, &bin_target, &target, config.app(), format)?;
+        info!("created package at file://{}", package_path.display());
+    }
+
     Ok(())
 }
@@ -0,0 +1,83 @@
+use clap::{ArgAction, Args, Subcommand};
+
+/// Arguments for inspecting the Bevy CLI config.
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    /// The subcommands available for the config command.
+    #[clap(subcommand)]
+    pub subcommand: ConfigSubcommands,
+}
+
+/// The subcommands available for the config command.
+#[derive(Debug, Subcommand)]
+pub enum ConfigSubcommands {
+    /// Show the resolved config and where each value comes from.
+    Show(ConfigShowArgs),
+    /// Check the config for unknown keys, which are otherwise only reported as warnings.
+    Check(ConfigCheckArgs),
+    /// Print the JSON Schema of the config, e.g. to validate and complete it in editors.
+    Schema,
+}
+
+/// Arguments for showing the resolved config.
+#[derive(Debug, Args)]
+pub struct ConfigShowArgs {
+    /// Show the config used for web builds.
+    #[arg(long = "web", action = ArgAction::SetTrue, default_value_t = false)]
+    pub is_web: bool,
+
+    /// Show the config used for release builds.
+    #[arg(short = 'r', long = "release", action = ArgAction::SetTrue, default_value_t = false)]
+    pub is_release: bool,
+
+    /// Apply a preset from the config on top of the other config, e.g. `--preset demo`.
+    ///
+    /// Presets are defined in the `preset.<NAME>` tables of the config.
+    #[arg(long = "preset", value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// The package to show the config of.
+    #[arg(short = 'p', long = "package", value_name = "SPEC")]
+    pub package: Option<String>,
+
+    /// Print the config and its sources as JSON.
+    #[arg(long = "json", action = ArgAction::SetTrue, default_value_t = false)]
+    pub json: bool,
+
+    /// The target triple, as passed to `bevy build`.
+    #[arg(long = "target", value_name = "TRIPLE")]
+    pub target: Option<String>,
+
+    /// Space or comma separated list of features, as passed to `bevy build`.
+    #[arg(short = 'F', long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Do not activate the `default` feature, as passed to `bevy build`.
+    #[arg(long = "no-default-features", action = ArgAction::SetTrue, default_value_t = false)]
+    pub is_no_default_features: bool,
+
+    /// Custom flags to pass to all compiler invocations, as passed to `bevy build`.
+    #[arg(long = "rustflags", allow_hyphen_values = true)]
+    pub rustflags: Option<String>,
+}
+
+/// Arguments for checking the config.
+#[derive(Debug, Args)]
+pub struct ConfigCheckArgs {
+    /// The package to check the config of.
+    #[arg(short = 'p', long = "package", value_name = "SPEC")]
+    pub package: Option<String>,
+}
+
+impl ConfigShowArgs {
+    /// The targeted platform, defaults to `wasm32-unknown-unknown` on web like `bevy build`.
+    pub(crate) fn target(&self) -> Option<String> {
+        if self.is_web {
+            self.target
+                .clone()
+                .or_else(|| Some("wasm32-unknown-unknown".to_owned()))
+        } else {
+            self.target.clone()
+        }
+    }
+}
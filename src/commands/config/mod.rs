@@ -0,0 +1,77 @@
+//! Inspecting the configuration of the Bevy CLI.
+
+use anyhow::bail;
+pub use args::*;
+use tracing::info;
+
+use crate::{
+    commands::get_package,
+    config::{CliConfig, ConfigReport},
+    config_schema::config_schema,
+    external_cli::cargo,
+};
+
+mod args;
+
+/// Runs the given config subcommand.
+pub fn config(args: &ConfigArgs) -> anyhow::Result<()> {
+    match &args.subcommand {
+        ConfigSubcommands::Show(show_args) => show(show_args),
+        ConfigSubcommands::Check(check_args) => check(check_args),
+        ConfigSubcommands::Schema => {
+            println!("{}", serde_json::to_string_pretty(&config_schema())?);
+            Ok(())
+        }
+    }
+}
+
+/// Print the resolved config, as it would be used by `bevy build`, and the sources of its values.
+fn show(args: &ConfigShowArgs) -> anyhow::Result<()> {
+    let metadata = cargo::metadata::metadata()?;
+    let package = get_package(&metadata, args.package.as_ref(), false, false)?;
+
+    let layers = CliConfig::layers(
+        &metadata,
+        package,
+        args.is_web,
+        args.is_release,
+        args.preset.as_deref(),
+        args.target.as_deref(),
+    )?;
+    let mut report = ConfigReport::from_layers(&layers)?;
+
+    let cargo_config = cargo_config2::Config::load()?;
+    report.append_cargo_config_rustflags(args.target(), &cargo_config)?;
+    report.append_linker_rustflags(args.target(), &cargo_config)?;
+
+    report.apply_cli_args(
+        args.target.as_deref(),
+        &args.features,
+        args.is_no_default_features,
+        args.rustflags.as_deref(),
+    );
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if report.config().is_default() {
+        info!("no config values are set, the defaults are used");
+    } else {
+        print!("{report}");
+    }
+
+    Ok(())
+}
+
+/// Fail if the config contains unknown keys, e.g. to catch typos in CI.
+fn check(args: &ConfigCheckArgs) -> anyhow::Result<()> {
+    let metadata = cargo::metadata::metadata()?;
+    let package = get_package(&metadata, args.package.as_ref(), false, false)?;
+
+    let messages = CliConfig::unknown_keys(&metadata, package)?;
+    if !messages.is_empty() {
+        bail!("{}", messages.join("\n"));
+    }
+
+    info!("the config contains no unknown keys");
+    Ok(())
+}
@@ -20,6 +20,12 @@ pub struct LintArgs {
     #[arg(long = "fix", default_value_t = false)]
     pub fix: bool,
 
+    /// Apply a preset from the config on top of the other config, e.g. `--preset demo`.
+    ///
+    /// Presets are defined in the `preset.<NAME>` tables of the config.
+    #[arg(long = "preset", value_name = "NAME")]
+    pub preset: Option<String>,
+
     /// Arguments to forward to `cargo check`.
     #[clap(flatten)]
     pub cargo_args: CargoCheckArgs,
@@ -111,14 +111,16 @@ fn build_lint_cmd(args: &mut LintArgs) -> anyhow::Result<CommandExt> {
         false,
     )?;
 
-    // apply the package specific config, otherwise use the default config (this happens when
-    // `bevy build` was called from a workspace root with no package selection (we do not support
-    // workspace config at the moment).
-    let mut config = if let Some(package) = package {
-        CliConfig::for_package(&metadata, package, args.is_web(), args.is_release())?
-    } else {
-        CliConfig::default()
-    };
+    // Apply the workspace config, overwritten by the package specific config. If no package is
+    // selected, e.g. in the root of a virtual workspace, only the workspace config is used.
+    let mut config = CliConfig::resolve(
+        &metadata,
+        package,
+        args.is_web(),
+        args.is_release(),
+        args.preset.as_deref(),
+        args.cargo_args.compilation_args.target.as_deref(),
+    )?;
 
     // Read config files hierarchically from the current directory, merge them,
     // apply environment variables, and resolve relative paths.
@@ -131,8 +133,8 @@ fn build_lint_cmd(args: &mut LintArgs) -> anyhow::Result<CommandExt> {
     if matches!(args.subcommand, Some(LintSubcommands::Web)) {
         use tracing::info;
 
-        use crate::web::{
-            getrandom::apply_getrandom_backend, profiles::configure_default_web_profiles,
+        use crate::{
+            profiles::configure_default_web_profiles, web::getrandom::apply_getrandom_backend,
         };
 
         let mut profile_args = configure_default_web_profiles(&metadata)?;
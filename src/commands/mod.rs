@@ -1,9 +1,12 @@
 //! All available commands for the Bevy CLI.
 
+use std::collections::HashSet;
+
 use cargo_metadata::{Metadata, Package, TargetKind};
 
 pub mod build;
 pub mod completions;
+pub mod config;
 pub mod lint;
 pub mod new;
 pub mod run;
@@ -52,10 +55,17 @@ fn get_package<'m>(
             })
             .collect();
 
+        let mut seen_packages = HashSet::new();
+        let bin_packages: Vec<_> = bins
+            .iter()
+            .filter(|package| seen_packages.insert(&package.id))
+            .collect();
+
         let bin_package = if bins.is_empty() {
             anyhow::bail!("No binaries available!");
-        } else if bins.len() == 1 {
-            bins[0]
+        } else if bin_packages.len() == 1 {
+            // The binary to run is selected later, e.g. with `--bin` or the `run-group` config
+            bin_packages[0]
         } else {
             // Otherwise, check if there is a default run target defined
             let default_runs: Vec<_> = packages
@@ -1,3 +1,6 @@
+#[cfg(feature = "https")]
+use std::path::PathBuf;
+
 #[cfg(feature = "web")]
 use clap::ArgAction;
 use clap::{Args, Subcommand};
@@ -5,6 +8,8 @@ use clap::{Args, Subcommand};
 use super::cargo::build::{CargoBuildArgs, CargoPackageBuildArgs, CargoTargetBuildArgs};
 #[cfg(feature = "web")]
 use crate::commands::build::{BuildSubcommands, BuildWebArgs};
+#[cfg(feature = "web")]
+use crate::web::compress::CompressionFormat;
 #[cfg(all(feature = "unstable", feature = "web"))]
 use crate::web::unstable::UnstableWebArgs;
 use crate::{
@@ -14,7 +19,10 @@ use crate::{
         arg_builder::ArgBuilder,
         cargo::{install::AutoInstall, run::CargoRunArgs},
     },
+    profiles::NATIVE_DIST_PROFILE,
 };
+#[cfg(feature = "https")]
+use crate::{config::HttpsConfig, web::https::Certificate};
 
 #[derive(Debug, Args, Clone)]
 pub struct RunArgs {
@@ -26,6 +34,16 @@ pub struct RunArgs {
     #[arg(long = "yes", default_value_t = false)]
     pub confirm_prompts: bool,
 
+    /// Rebuild and restart the app when its sources change.
+    #[arg(short = 'w', long = "watch", default_value_t = false)]
+    pub watch: bool,
+
+    /// Apply a preset from the config on top of the other config, e.g. `--preset demo`.
+    ///
+    /// Presets are defined in the `preset.<NAME>` tables of the config.
+    #[arg(long = "preset", value_name = "NAME")]
+    pub preset: Option<String>,
+
     /// Commands to forward to `cargo run`.
     #[clap(flatten)]
     pub cargo_args: CargoRunArgs,
@@ -63,9 +81,24 @@ impl RunArgs {
     pub(crate) fn is_release(&self) -> bool {
         self.cargo_args.compilation_args.profile.as_deref() == Some("release")
             || self.cargo_args.compilation_args.profile.as_deref() == Some("web-release")
+            || self.cargo_args.compilation_args.profile.as_deref() == Some(NATIVE_DIST_PROFILE)
             || self.cargo_args.compilation_args.is_release
     }
 
+    /// Whether the app is built natively with the `dev` profile.
+    pub(crate) fn is_native_dev(&self) -> bool {
+        !self.is_web()
+            && self.cargo_args.compilation_args.profile(self.is_web()) == "dev"
+            && !self
+                .target()
+                .is_some_and(|target| target.starts_with("wasm"))
+    }
+
+    /// Whether several binaries should be run at once.
+    pub(crate) fn is_group(&self) -> bool {
+        self.cargo_args.target_args.bin.len() > 1
+    }
+
     /// The targeted platform.
     pub(crate) fn target(&self) -> Option<String> {
         self.cargo_args.compilation_args.target(self.is_web())
@@ -110,6 +143,21 @@ impl RunArgs {
             .clone()
             .or(config.rustflags());
 
+        // Arguments passed after `--` replace the default arguments
+        if self.forward_args.is_empty() {
+            self.forward_args = config.run_args().to_vec();
+        }
+
+        if !self.is_web()
+            && self.cargo_args.target_args.bin.is_empty()
+            && self.cargo_args.target_args.example.is_none()
+        {
+            self.cargo_args
+                .target_args
+                .bin
+                .extend(config.run_group().iter().cloned());
+        }
+
         #[cfg(feature = "web")]
         let is_release = self.is_release();
 
@@ -119,7 +167,18 @@ impl RunArgs {
                 web_args.wasm_opt = config.wasm_opt(is_release).to_raw();
             }
 
+            web_args.port = web_args.port.or(config.port());
+            if web_args.host.is_none() {
+                web_args.host = config.host().map(ToOwned::to_owned);
+            }
+            web_args.open |= config.open();
+
             web_args.headers.extend(config.headers());
+            // Rules passed via the CLI take precedence, because they come first
+            web_args.proxy.extend(config.proxy_rules());
+
+            #[cfg(feature = "https")]
+            web_args.apply_https_config(config);
 
             #[cfg(feature = "unstable")]
             web_args.unstable.apply_config(config);
@@ -135,15 +194,19 @@ pub enum RunSubcommands {
 }
 
 #[cfg(feature = "web")]
-#[derive(Debug, Args, Clone)]
+#[derive(Debug, Args, Clone, Default)]
 pub struct RunWebArgs {
     /// The port to run the web server on.
-    #[arg(short, long, default_value_t = 4000)]
-    pub port: u16,
+    ///
+    /// Defaults to `4000`.
+    #[arg(short, long)]
+    pub port: Option<u16>,
 
-    /// The host address to run the web server on
-    #[arg(long, default_value_t = String::from("127.0.0.1"))]
-    pub host: String,
+    /// The host address to run the web server on.
+    ///
+    /// Defaults to `127.0.0.1`.
+    #[arg(long)]
+    pub host: Option<String>,
 
     /// Open the app in the browser.
     #[arg(short = 'o', long = "open", action = ArgAction::SetTrue, default_value_t = false)]
@@ -153,12 +216,51 @@ pub struct RunWebArgs {
     #[arg(short = 'b', long = "bundle", action = ArgAction::SetTrue, default_value_t = false)]
     pub create_packed_bundle: bool,
 
+    /// Create pre-compressed copies of the bundled files, e.g. `--compress gzip,brotli`.
+    ///
+    /// The web server serves them to browsers that support the compression.
+    #[arg(
+        long = "compress",
+        value_name = "FORMAT",
+        value_delimiter = ',',
+        requires = "create_packed_bundle"
+    )]
+    pub compress: Vec<CompressionFormat>,
+
+    /// Rebuild the app and reload the page when the sources, assets or web assets change.
+    #[arg(short = 'w', long = "watch", action = ArgAction::SetTrue, default_value_t = false, conflicts_with = "create_packed_bundle")]
+    pub watch: bool,
+
+    /// Serve the app over HTTPS.
+    ///
+    /// Uses a self-signed certificate, unless `--https-cert` and `--https-key` are provided.
+    #[cfg(feature = "https")]
+    #[arg(long = "https", action = ArgAction::SetTrue, default_value_t = false)]
+    pub https: bool,
+
+    /// The path to the certificate to use for HTTPS, in the PEM format.
+    #[cfg(feature = "https")]
+    #[arg(long = "https-cert", value_name = "PATH", requires = "https_key")]
+    pub https_cert: Option<PathBuf>,
+
+    /// The path to the private key to use for HTTPS, in the PEM format.
+    #[cfg(feature = "https")]
+    #[arg(long = "https-key", value_name = "PATH", requires = "https_cert")]
+    pub https_key: Option<PathBuf>,
+
     /// Headers to add to the web-server responses, in the format `name:value` or `name=value`.
     ///
     /// Can be defined multiple times to add multiple headers.
     #[clap(short = 'H', long = "headers", value_name = "HEADERS")]
     pub headers: Vec<String>,
 
+    /// Forward requests to another server, in the format `/path=http://host:port`.
+    ///
+    /// Can be defined multiple times to add multiple rules.
+    /// Useful to access a local backend from the same origin as the app.
+    #[arg(long = "proxy", value_name = "PATH=URL")]
+    pub proxy: Vec<String>,
+
     /// Use `wasm-opt` to optimize the wasm binary
     ///
     /// Defaults to `true` for release builds.
@@ -173,17 +275,45 @@ pub struct RunWebArgs {
 }
 
 #[cfg(feature = "web")]
-impl Default for RunWebArgs {
-    fn default() -> Self {
-        Self {
-            port: 4000,
-            host: String::from("127.0.0.1"),
-            open: false,
-            create_packed_bundle: false,
-            headers: Vec::new(),
-            wasm_opt: Vec::new(),
-            #[cfg(feature = "unstable")]
-            unstable: UnstableWebArgs::default(),
+impl RunWebArgs {
+    /// The port to run the web server on.
+    pub(crate) fn port(&self) -> u16 {
+        self.port.unwrap_or(4000)
+    }
+
+    /// The host address to run the web server on.
+    pub(crate) fn host(&self) -> &str {
+        self.host.as_deref().unwrap_or("127.0.0.1")
+    }
+
+    /// The certificate to use if the app should be served over HTTPS.
+    #[cfg(feature = "https")]
+    pub(crate) fn https_certificate(&self) -> Option<Certificate> {
+        match (&self.https_cert, &self.https_key) {
+            (Some(cert), Some(key)) => Some(Certificate::Files {
+                cert: cert.clone(),
+                key: key.clone(),
+            }),
+            _ if self.https => Some(Certificate::SelfSigned),
+            _ => None,
+        }
+    }
+
+    /// Apply the HTTPS settings from the config, unless HTTPS has been configured via the CLI.
+    #[cfg(feature = "https")]
+    fn apply_https_config(&mut self, config: &CliConfig) {
+        if self.https || self.https_cert.is_some() {
+            return;
+        }
+
+        match config.https() {
+            Some(HttpsConfig::Enabled(enabled)) => self.https = *enabled,
+            Some(HttpsConfig::Certificate { cert, key }) => {
+                self.https = true;
+                self.https_cert = Some(cert.clone());
+                self.https_key = Some(key.clone());
+            }
+            None => {}
         }
     }
 }
@@ -192,6 +322,14 @@ impl From<RunArgs> for BuildArgs {
     fn from(args: RunArgs) -> Self {
         BuildArgs {
             confirm_prompts: args.confirm_prompts,
+            create_bundle: false,
+            preset: args.preset,
+            #[cfg(feature = "archive")]
+            archive: None,
+            #[cfg(feature = "archive")]
+            archive_path: None,
+            #[cfg(feature = "archive")]
+            package_format: None,
             cargo_args: CargoBuildArgs {
                 common_args: args.cargo_args.common_args,
                 compilation_args: args.cargo_args.compilation_args,
@@ -203,7 +341,8 @@ impl From<RunArgs> for BuildArgs {
                     exclude: None,
                 },
                 target_args: CargoTargetBuildArgs {
-                    bin: args.cargo_args.target_args.bin,
+                    // Running several binaries at once builds them separately, see `run_group`
+                    bin: args.cargo_args.target_args.bin.into_iter().next(),
                     example: args.cargo_args.target_args.example,
                     is_all_targets: false,
                     is_benches: false,
@@ -224,6 +363,14 @@ impl From<RunArgs> for BuildArgs {
                     #[cfg(feature = "unstable")]
                     unstable: web_args.unstable,
                     bundle_dir: None,
+                    compress: web_args.compress,
+                    hash_filenames: false,
+                    hash_assets: false,
+                    size_report: false,
+                    #[cfg(feature = "archive")]
+                    archive: None,
+                    #[cfg(feature = "archive")]
+                    archive_path: None,
                 }),
             }),
         }
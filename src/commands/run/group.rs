@@ -0,0 +1,279 @@
+//! Running several binaries at once, e.g. a server together with multiple clients.
+
+use std::{
+    io::{self, BufRead as _, BufReader, IsTerminal as _, Read, Write},
+    process::{Child, ExitStatus, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use ansi_term::Color;
+use anyhow::{Context as _, bail};
+use cargo_metadata::Metadata;
+use tracing::{debug, info};
+
+use super::RunArgs;
+use crate::{
+    bin_target::select_run_binary,
+    commands::build::BuildArgs,
+    external_cli::{arg_builder::ArgBuilder, cargo},
+};
+
+/// The colors used for the output prefixes of the processes, in order.
+const PREFIX_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Green,
+    Color::Purple,
+    Color::Blue,
+    Color::Red,
+];
+
+/// A running binary of the group.
+struct Process {
+    /// The label shown in front of every line of output.
+    label: String,
+    child: Child,
+    /// The threads forwarding stdout and stderr.
+    output_threads: Vec<JoinHandle<()>>,
+}
+
+/// Build all binaries of the group at once, then run each of them as its own process.
+///
+/// The output of each process is prefixed with its label.
+/// As soon as one process exits or Ctrl+C is pressed, all processes are stopped.
+pub(crate) fn run_group(
+    args: &RunArgs,
+    metadata: &Metadata,
+    env: &[(String, String)],
+) -> anyhow::Result<()> {
+    let bins = &args.cargo_args.target_args.bin;
+    let build_args: BuildArgs = args.clone().into();
+
+    let bin_targets = bins
+        .iter()
+        .map(|bin| {
+            select_run_binary(
+                metadata,
+                build_args.cargo_args.package_args.package.as_deref(),
+                Some(bin),
+                None,
+                build_args.target().as_deref(),
+                build_args.profile(),
+            )
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    cargo::build::command()
+        .args(group_cargo_args(args))
+        .env("RUSTFLAGS", build_args.rustflags())
+        .ensure_status(build_args.auto_install())?;
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    {
+        let interrupted = Arc::clone(&interrupted);
+        ctrlc::set_handler(move || interrupted.store(true, Ordering::SeqCst))
+            .context("failed to listen for Ctrl+C")?;
+    }
+
+    let use_colors = io::stdout().is_terminal();
+    let mut processes = Vec::new();
+
+    for (index, (bin_target, label)) in bin_targets.iter().zip(process_labels(bins)).enumerate() {
+        let prefix = if use_colors {
+            PREFIX_COLORS[index % PREFIX_COLORS.len()]
+                .bold()
+                .paint(format!("[{label}]"))
+                .to_string()
+        } else {
+            format!("[{label}]")
+        };
+
+        info!(
+            "running `{}` as {label}",
+            bin_target.executable_path().display()
+        );
+        let spawned = bin_target
+            .command()?
+            .args(&args.forward_args)
+            .envs(env.iter().cloned())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run {label}"));
+
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(error) => {
+                stop_processes(&mut processes);
+                return Err(error);
+            }
+        };
+
+        let mut output_threads = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            output_threads.push(forward_output(stdout, prefix.clone(), io::stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            output_threads.push(forward_output(stderr, prefix, io::stderr));
+        }
+
+        processes.push(Process {
+            label,
+            child,
+            output_threads,
+        });
+    }
+
+    let exited = wait_for_first_exit(&mut processes, &interrupted)?;
+    stop_processes(&mut processes);
+
+    match exited {
+        // The processes also receive Ctrl+C, so they might exit before the CLI notices it
+        _ if interrupted.load(Ordering::SeqCst) => Ok(()),
+        Some((label, status)) if !status.success() => bail!("{label} exited with {status}"),
+        _ => Ok(()),
+    }
+}
+
+/// Wait until one of the processes exits or Ctrl+C is pressed.
+///
+/// Returns the label and exit status of the process that exited first.
+fn wait_for_first_exit(
+    processes: &mut [Process],
+    interrupted: &AtomicBool,
+) -> anyhow::Result<Option<(String, ExitStatus)>> {
+    loop {
+        if interrupted.load(Ordering::SeqCst) {
+            info!("received Ctrl+C, stopping all processes...");
+            return Ok(None);
+        }
+
+        for process in processes.iter_mut() {
+            if let Some(status) = process.child.try_wait()? {
+                info!(
+                    "{} exited with {status}, stopping the other processes...",
+                    process.label
+                );
+                return Ok(Some((process.label.clone(), status)));
+            }
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Stop all processes that are still running and wait until their output has been forwarded.
+fn stop_processes(processes: &mut Vec<Process>) {
+    for mut process in processes.drain(..) {
+        if let Ok(None) = process.child.try_wait() {
+            debug!("stopping {}", process.label);
+            // The process might exit in the meantime, in which case there is nothing to kill
+            let _ = process.child.kill();
+        }
+        let _ = process.child.wait();
+
+        for thread in process.output_threads {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Forward the output of a process line by line, with the prefix in front of every line.
+fn forward_output<W: Write + 'static>(
+    output: impl Read + Send + 'static,
+    prefix: String,
+    writer: fn() -> W,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+
+        while let Ok(length) = reader.read_until(b'\n', &mut line) {
+            if length == 0 {
+                break;
+            }
+
+            let text = String::from_utf8_lossy(&line);
+            // Each line is written at once, so lines of different processes aren't mixed up
+            let _ = writeln!(writer(), "{prefix} {}", text.trim_end_matches(['\r', '\n']));
+            line.clear();
+        }
+    })
+}
+
+/// The args for building all binaries of the group with a single `cargo build`.
+///
+/// `cargo build` accepts multiple binaries, so each of them is only passed once.
+fn group_cargo_args(args: &RunArgs) -> ArgBuilder {
+    let mut build_args: BuildArgs = args.clone().into();
+    // The build args only keep the first binary, which is replaced by the whole group
+    build_args.cargo_args.target_args.bin = None;
+
+    let mut unique_bins = args.cargo_args.target_args.bin.clone();
+    unique_bins.sort();
+    unique_bins.dedup();
+
+    build_args
+        .cargo_args_builder()
+        .add_values_separately("--bin", unique_bins)
+}
+
+/// The labels of the processes, used as prefixes of their output.
+///
+/// Binaries that are run multiple times are numbered, e.g. `client#1` and `client#2`.
+fn process_labels(bins: &[String]) -> Vec<String> {
+    bins.iter()
+        .enumerate()
+        .map(|(index, bin)| {
+            if bins.iter().filter(|other| *other == bin).count() > 1 {
+                let number = bins[..=index].iter().filter(|other| *other == bin).count();
+                format!("{bin}#{number}")
+            } else {
+                bin.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        run_args: RunArgs,
+    }
+
+    #[test]
+    fn should_pass_each_binary_once() {
+        let args = Cli::parse_from([
+            "bevy", "--bin", "server", "--bin", "client", "--bin", "client",
+        ])
+        .run_args;
+
+        let cargo_args: Vec<_> = group_cargo_args(&args).into_iter().collect();
+
+        assert_eq!(
+            cargo_args,
+            ["--profile", "dev", "--bin", "client", "--bin", "server"]
+        );
+    }
+
+    #[test]
+    fn should_number_duplicate_binaries() {
+        let bins = ["server", "client", "client"].map(ToOwned::to_owned);
+
+        assert_eq!(
+            process_labels(&bins),
+            vec!["server", "client#1", "client#2"]
+        );
+    }
+}
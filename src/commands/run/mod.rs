@@ -1,13 +1,22 @@
 //! Provides functionalities to run a Bevy app targeting either native or web platforms.
 
+use std::env;
+
+use anyhow::bail;
 use tracing::info;
 
 pub use self::args::*;
+use self::{group::run_group, watch::run_watch};
 #[cfg(feature = "web")]
 use crate::web::run::run_web;
-use crate::{commands::get_package, config::CliConfig, external_cli::cargo};
+use crate::{
+    commands::get_package, config::CliConfig, dynamic_linking::bevy_dynamic_linking_feature,
+    external_cli::cargo, profiles::configure_default_native_profiles,
+};
 
 mod args;
+mod group;
+mod watch;
 
 /// Tries to run the project with the given [`RunArgs`].
 ///
@@ -24,22 +33,36 @@ pub fn run(args: &mut RunArgs) -> anyhow::Result<()> {
         true,
     )?;
 
-    // apply the package specific config, otherwise use the default config (this happens when
-    // `bevy build` was called from a workspace root with no package selection (we do not support
-    // workspace config at the moment).
-    let mut config = if let Some(package) = package {
-        CliConfig::for_package(&metadata, package, args.is_web(), args.is_release())?
-    } else {
-        CliConfig::default()
-    };
+    // Apply the workspace config, overwritten by the package specific config. If no package is
+    // selected, e.g. in the root of a virtual workspace, only the workspace config is used.
+    let mut config = CliConfig::resolve(
+        &metadata,
+        package,
+        args.is_web(),
+        args.is_release(),
+        args.preset.as_deref(),
+        args.cargo_args.compilation_args.target.as_deref(),
+    )?;
 
     // Read config files hierarchically from the current directory, merge them,
     // apply environment variables, and resolve relative paths.
     let cargo_config = cargo_config2::Config::load()?;
     config.append_cargo_config_rustflags(args.target(), &cargo_config)?;
+    config.append_linker_rustflags(args.target(), &cargo_config)?;
 
     args.apply_config(&config);
 
+    // Dynamic linking only speeds up iteration, it must not leak into release or web builds
+    if config.dynamic_linking()
+        && args.is_native_dev()
+        && let Some(package) = package
+        && let Some(feature) = bevy_dynamic_linking_feature(&metadata, package)
+        && !args.cargo_args.feature_args.features.contains(&feature)
+    {
+        info!("enabling {feature}");
+        args.cargo_args.feature_args.features.push(feature);
+    }
+
     // Extend the already present features with the required_features from this example.
     if let Some(example) = &args.cargo_args.target_args.example
     // Search in the current workspace packages for an `example` target that matches the given
@@ -65,7 +88,41 @@ pub fn run(args: &mut RunArgs) -> anyhow::Result<()> {
 
     #[cfg(feature = "web")]
     if args.is_web() {
-        return run_web(args, &metadata);
+        if args.is_group() {
+            bail!("running multiple binaries at once is only supported for native apps");
+        }
+
+        // `bevy run --watch web` is treated like `bevy run web --watch`
+        if let Some(RunSubcommands::Web(web_args)) = &mut args.subcommand {
+            web_args.watch |= args.watch;
+
+            // The conflict of `--watch` and `--bundle` is only checked by clap for the web args
+            if web_args.watch && web_args.create_packed_bundle {
+                bail!("`--watch` can't be combined with `--bundle`");
+            }
+        }
+
+        return run_web(args, &metadata, config.env().clone());
+    }
+
+    let mut profile_args = configure_default_native_profiles(&metadata)?;
+    // `--config` args are resolved from left to right,
+    // so the default configuration needs to come before the user args
+    profile_args.append(&mut args.cargo_args.common_args.config);
+    args.cargo_args.common_args.config = profile_args;
+
+    let app_env = app_env(&config);
+
+    if args.is_group() {
+        if args.watch {
+            bail!("`--watch` can't be combined with running multiple binaries");
+        }
+
+        return run_group(args, &metadata, &app_env);
+    }
+
+    if args.watch {
+        return run_watch(args, &metadata, &app_env);
     }
 
     let cargo_args = args.cargo_args_builder();
@@ -74,7 +131,20 @@ pub fn run(args: &mut RunArgs) -> anyhow::Result<()> {
     cargo::run::command()
         .args(cargo_args)
         .env("RUSTFLAGS", args.cargo_args.common_args.rustflags.clone())
+        .envs(app_env)
         .ensure_status(args.auto_install())?;
 
     Ok(())
 }
+
+/// The environment variables from the config to set for the native app.
+///
+/// Variables that are already set, e.g. in the shell, take precedence over the config.
+fn app_env(config: &CliConfig) -> Vec<(String, String)> {
+    config
+        .env()
+        .iter()
+        .filter(|(name, _)| env::var_os(name).is_none())
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
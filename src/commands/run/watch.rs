@@ -0,0 +1,99 @@
+//! Rebuilding and restarting native apps whenever their sources change.
+
+use std::process::Child;
+
+use anyhow::Context as _;
+use cargo_metadata::Metadata;
+use tracing::{debug, error, info};
+
+use super::RunArgs;
+use crate::{
+    bin_target::{BinTarget, select_run_binary},
+    commands::build::BuildArgs,
+    external_cli::cargo,
+    watch::{FileWatcher, watched_source_paths},
+};
+
+/// Build and run the app, then rebuild and restart it whenever one of its sources changes.
+///
+/// Build errors are printed, but don't stop the watcher.
+/// This function only returns if the binary can't be determined.
+pub(crate) fn run_watch(
+    args: &RunArgs,
+    metadata: &Metadata,
+    env: &[(String, String)],
+) -> anyhow::Result<()> {
+    let build_args: BuildArgs = args.clone().into();
+
+    let bin_target = select_run_binary(
+        metadata,
+        build_args.cargo_args.package_args.package.as_deref(),
+        build_args.cargo_args.target_args.bin.as_deref(),
+        build_args.cargo_args.target_args.example.as_deref(),
+        build_args.target().as_deref(),
+        build_args.profile(),
+    )?;
+
+    let mut watcher = FileWatcher::new(watched_source_paths(metadata, bin_target.package));
+    debug!("watching for changes in {:?}", watcher.paths());
+
+    loop {
+        let mut app = build(&build_args)
+            .and_then(|()| spawn_app(&bin_target, &args.forward_args, env))
+            .inspect_err(|error| error!("{error:#}"))
+            .ok();
+
+        info!("watching for changes...");
+        let changes = watcher.wait_for_changes();
+        debug!("changed files: {changes:?}");
+        info!(
+            "detected changes in {} file(s), rebuilding...",
+            changes.len()
+        );
+
+        if let Some(app) = &mut app {
+            stop_app(app);
+        }
+    }
+}
+
+/// Compile the app with `cargo build`.
+fn build(args: &BuildArgs) -> anyhow::Result<()> {
+    cargo::build::command()
+        .args(args.cargo_args_builder())
+        .env("RUSTFLAGS", args.rustflags())
+        .ensure_status(args.auto_install())
+        .context("failed to build the app")?;
+
+    Ok(())
+}
+
+/// Launch the compiled binary.
+fn spawn_app(
+    bin_target: &BinTarget,
+    forward_args: &[String],
+    env: &[(String, String)],
+) -> anyhow::Result<Child> {
+    let path = bin_target.executable_path();
+    info!("running `{}`", path.display());
+
+    bin_target
+        .command()?
+        .args(forward_args)
+        .envs(env.iter().cloned())
+        .spawn()
+        .with_context(|| format!("failed to run {}", path.display()))
+}
+
+/// Stop the app if it's still running.
+fn stop_app(app: &mut Child) {
+    match app.try_wait() {
+        Ok(Some(status)) => debug!("app already exited with {status}"),
+        _ => {
+            info!("stopping the app...");
+            // The app might exit in the meantime, in which case there is nothing to kill
+            let _ = app.kill();
+            let _ = app.wait();
+        }
+    }
+}
@@ -1,13 +1,25 @@
-//! Configuration used by the `bevy_cli`, defined in `Cargo.toml` under `package.metadata.bevy_cli`.
-use std::fmt::Display;
-
-use anyhow::{Context, bail};
+//! Configuration used by the `bevy_cli`, defined in `Cargo.toml` under `package.metadata.bevy_cli`
+//! or in config files like `.bevy/config.toml`.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail};
 use cargo_metadata::{Metadata, Package};
 use serde::Serialize;
 use serde_json::{Map, Value};
-use tracing::warn;
+use tracing::{debug, warn};
 
-use crate::external_cli::external_cli_args::ExternalCliArgs;
+use crate::{
+    config_schema::{config_keys, env_value, unknown_keys},
+    external_cli::external_cli_args::ExternalCliArgs,
+    linker::{Linker, linker_rustflags},
+    target_cfg::TargetCfg,
+};
 
 /// Configuration for the `bevy_cli`.
 ///
@@ -33,6 +45,166 @@ pub struct CliConfig {
     wasm_opt: Option<ExternalCliArgs>,
     /// EXPERIMENTAL: Enable building and running apps that use Wasm multi-threading features.
     web_multi_threading: Option<bool>,
+    /// Serve the app over HTTPS.
+    https: Option<HttpsConfig>,
+    /// Forward requests with the given path prefixes to other servers.
+    proxy: BTreeMap<String, String>,
+    /// Link Bevy dynamically in native dev builds.
+    dynamic_linking: Option<bool>,
+    /// The linker to use for native builds.
+    linker: Option<Linker>,
+    /// Additional files to copy into native bundles, relative to the package root.
+    bundle_files: Vec<PathBuf>,
+    /// Metadata of the app used for packaging.
+    app: AppConfig,
+    /// The binaries to run together if no binary is passed to `bevy run`.
+    run_group: Option<Vec<String>>,
+    /// Environment variables to set for the app started by `bevy run`.
+    env: BTreeMap<String, String>,
+    /// The default arguments passed to the app started by `bevy run`.
+    run_args: Vec<String>,
+    /// The port to run the web server on.
+    port: Option<u16>,
+    /// The host address to run the web server on.
+    host: Option<String>,
+    /// Open the app in the browser.
+    open: Option<bool>,
+}
+
+/// Metadata of the app used for packaging, e.g. in Linux desktop entries.
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AppConfig {
+    /// The human-readable name of the app.
+    pub display_name: Option<String>,
+    /// The icon of the app, relative to the package root.
+    pub icon: Option<PathBuf>,
+    /// The categories of the app, as defined by the freedesktop.org menu specification.
+    pub categories: Option<Vec<String>>,
+}
+
+impl AppConfig {
+    /// Merge another app config into this one, the other config takes precedence.
+    fn overwrite(self, with: &Self) -> Self {
+        Self {
+            display_name: with.display_name.clone().or(self.display_name),
+            icon: with.icon.clone().or(self.icon),
+            categories: with.categories.clone().or(self.categories),
+        }
+    }
+}
+
+/// Whether to serve the app over HTTPS and which certificate to use.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum HttpsConfig {
+    /// Enable or disable HTTPS, using a self-signed certificate.
+    Enabled(bool),
+    /// Enable HTTPS with a custom certificate and private key in the PEM format.
+    Certificate { cert: PathBuf, key: PathBuf },
+}
+
+/// A part of the configuration that is merged with the others, e.g. the config for web builds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigLayer {
+    /// Where the layer is defined, e.g. `package.metadata.bevy_cli.web`.
+    pub source: String,
+    /// The values defined in the layer.
+    pub config: CliConfig,
+}
+
+/// A table that defines the config of the Bevy CLI.
+#[derive(Debug, Clone, Copy)]
+enum ConfigTable<'a> {
+    /// A metadata table of a `Cargo.toml`, e.g. `package.metadata.bevy_cli`.
+    Metadata(&'a str),
+    /// A config file, e.g. `.bevy/config.toml`.
+    File(&'a Path),
+}
+
+impl ConfigTable<'_> {
+    /// The name of a key of the table, e.g. `package.metadata.bevy_cli.web` or
+    /// `web in .bevy/config.toml`.
+    ///
+    /// An empty key refers to the table itself.
+    fn name(&self, key: &str) -> String {
+        match self {
+            Self::Metadata(table) if key.is_empty() => (*table).to_owned(),
+            Self::Metadata(table) => format!("{table}.{key}"),
+            Self::File(path) if key.is_empty() => path.display().to_string(),
+            Self::File(path) => format!("{key} in {}", path.display()),
+        }
+    }
+
+    /// The name of a key of the table, formatted for messages.
+    fn describe(&self, key: &str) -> String {
+        match self {
+            Self::File(path) if !key.is_empty() => format!("`{key}` in `{}`", path.display()),
+            _ => format!("`{}`", self.name(key)),
+        }
+    }
+}
+
+/// A config table together with its values.
+#[derive(Debug, Clone, Copy)]
+struct LoadedTable<'a> {
+    /// Where the table is defined.
+    table: ConfigTable<'a>,
+    /// The values of the table, if it exists.
+    values: Option<&'a Value>,
+    /// The directory that relative paths in the table are relative to.
+    root: &'a Path,
+}
+
+/// A config file of the Bevy CLI, e.g. `.bevy/config.toml`.
+#[derive(Debug, Clone)]
+struct ConfigFile {
+    /// The path of the file.
+    path: PathBuf,
+    /// The directory that relative paths in the file are relative to.
+    root: PathBuf,
+    /// The contents of the file.
+    values: Value,
+}
+
+impl LoadedTable<'_> {
+    /// Messages for the unknown keys of the table, with suggestions for likely typos.
+    fn unknown_keys(&self) -> Vec<String> {
+        let Some(values) = self.values else {
+            return Vec::new();
+        };
+
+        unknown_keys(values)
+            .into_iter()
+            .map(|unknown_key| {
+                let key = self.table.describe(&unknown_key.key);
+                match unknown_key.suggestion {
+                    Some(suggestion) => format!("unknown key {key}, did you mean `{suggestion}`?"),
+                    None => format!("unknown key {key}"),
+                }
+            })
+            .collect()
+    }
+}
+
+impl ConfigFile {
+    /// The config table defined by the file.
+    fn table(&self) -> LoadedTable<'_> {
+        LoadedTable {
+            table: ConfigTable::File(&self.path),
+            values: Some(&self.values),
+            root: &self.root,
+        }
+    }
+}
+
+/// The resolved config together with the sources of its values.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReport {
+    /// The config after merging all sources.
+    config: CliConfig,
+    /// The sources of each configured key, in the order they were applied.
+    sources: BTreeMap<String, Vec<String>>,
 }
 
 impl CliConfig {
@@ -47,6 +219,18 @@ impl CliConfig {
             wasm_opt,
             web_multi_threading,
             headers,
+            https,
+            proxy,
+            dynamic_linking,
+            linker,
+            bundle_files,
+            app,
+            run_group,
+            env,
+            run_args,
+            port,
+            host,
+            open,
         } = self;
 
         target.is_none()
@@ -56,6 +240,18 @@ impl CliConfig {
             && wasm_opt.is_none()
             && web_multi_threading.is_none()
             && headers.is_empty()
+            && https.is_none()
+            && proxy.is_empty()
+            && dynamic_linking.is_none()
+            && linker.is_none()
+            && bundle_files.is_empty()
+            && *app == AppConfig::default()
+            && run_group.is_none()
+            && env.is_empty()
+            && run_args.is_empty()
+            && port.is_none()
+            && host.is_none()
+            && open.is_none()
     }
 
     /// The platform to target with the build.
@@ -108,66 +304,396 @@ impl CliConfig {
         self.headers.clone()
     }
 
-    /// Determine the Bevy CLI config as defined in the given package.
-    pub fn for_package(
+    /// Whether to serve the app over HTTPS and which certificate to use.
+    #[cfg(feature = "https")]
+    pub fn https(&self) -> Option<&HttpsConfig> {
+        self.https.as_ref()
+    }
+
+    /// The proxy rules of the web server, in the format `/path=http://host:port`.
+    #[cfg(feature = "web")]
+    pub fn proxy_rules(&self) -> Vec<String> {
+        self.proxy
+            .iter()
+            .map(|(path, upstream)| format!("{path}={upstream}"))
+            .collect()
+    }
+
+    /// Whether to link Bevy dynamically in native dev builds.
+    ///
+    /// Defaults to `false` if not configured otherwise.
+    pub fn dynamic_linking(&self) -> bool {
+        self.dynamic_linking.unwrap_or(false)
+    }
+
+    /// Additional files to copy into native bundles.
+    pub fn bundle_files(&self) -> &[PathBuf] {
+        &self.bundle_files
+    }
+
+    /// Metadata of the app used for packaging.
+    #[cfg(feature = "archive")]
+    pub fn app(&self) -> &AppConfig {
+        &self.app
+    }
+
+    /// The binaries to run together if no binary is passed to `bevy run`.
+    pub fn run_group(&self) -> &[String] {
+        self.run_group.as_deref().unwrap_or_default()
+    }
+
+    /// Environment variables to set for the app started by `bevy run`.
+    pub fn env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    /// The default arguments passed to the app started by `bevy run`.
+    pub fn run_args(&self) -> &[String] {
+        &self.run_args
+    }
+
+    /// The port to run the web server on.
+    #[cfg(feature = "web")]
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// The host address to run the web server on.
+    #[cfg(feature = "web")]
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// Whether to open the app in the browser.
+    ///
+    /// Defaults to `false` if not configured otherwise.
+    #[cfg(feature = "web")]
+    pub fn open(&self) -> bool {
+        self.open.unwrap_or(false)
+    }
+
+    /// Determine the Bevy CLI config for the given package.
+    ///
+    /// The `workspace.metadata.bevy_cli` table is used as base, which is overwritten by the
+    /// `package.metadata.bevy_cli` table of the package.
+    /// If no package is selected, e.g. in the root of a virtual workspace, only the workspace
+    /// config is used.
+    /// The selected preset is applied on top.
+    /// The `target` sections are matched against the given target, which defaults to the
+    /// configured target, `wasm32-unknown-unknown` for web builds or the host platform.
+    pub fn resolve(
         metadata: &Metadata,
-        package: &Package,
+        package: Option<&Package>,
         is_web: bool,
         is_release: bool,
+        preset: Option<&str>,
+        target: Option<&str>,
     ) -> anyhow::Result<Self> {
-        let Some(package_metadata) = metadata.packages.iter().find_map(|cur_package| {
-            if package == cur_package {
-                Some(cur_package.metadata.clone())
-            } else {
-                None
+        Ok(Self::merge_layers(&Self::layers(
+            metadata, package, is_web, is_release, preset, target,
+        )?))
+    }
+
+    /// The layers the config of the given package is merged from, from general to specific.
+    ///
+    /// The layers of the config files come first, followed by the layers of the
+    /// `workspace.metadata.bevy_cli` table and the `package.metadata.bevy_cli` table.
+    /// The layers of the selected preset are applied next and the `BEVY_CLI_*` environment
+    /// variables last.
+    pub fn layers(
+        metadata: &Metadata,
+        package: Option<&Package>,
+        is_web: bool,
+        is_release: bool,
+        preset: Option<&str>,
+        target: Option<&str>,
+    ) -> anyhow::Result<Vec<ConfigLayer>> {
+        let current_dir =
+            env::current_dir().context("failed to determine the current directory")?;
+        let files = read_config_files(&current_dir, user_config_dir().as_deref())?;
+        let tables = loaded_tables(&files, metadata, package);
+
+        // Typos would otherwise be silently ignored
+        for message in tables.iter().flat_map(LoadedTable::unknown_keys) {
+            warn!("{message}");
+        }
+
+        let env_layers =
+            Self::env_layers(|name| env::var(name).ok(), &current_dir, is_web, is_release)?;
+
+        // `rustc` is only queried if the config has target sections
+        let target_cfg = if tables
+            .iter()
+            .any(|table| extract_target_sections(table.values).is_some())
+        {
+            let target = match target {
+                Some(target) => Some(target.to_owned()),
+                // The targeted platform can be configured itself, outside of the target sections
+                None => {
+                    let mut layers = Self::table_layers(&tables, is_web, is_release, preset, None)?;
+                    layers.extend(env_layers.iter().cloned());
+                    Self::merge_layers(&layers)
+                        .target()
+                        .or(is_web.then_some("wasm32-unknown-unknown"))
+                        .map(ToOwned::to_owned)
+                }
+            };
+            let target_cfg = TargetCfg::query(target.as_deref())?;
+            debug!(
+                "matching the target sections of the config against `{}`",
+                target_cfg.triple()
+            );
+            Some(target_cfg)
+        } else {
+            None
+        };
+
+        let mut layers =
+            Self::table_layers(&tables, is_web, is_release, preset, target_cfg.as_ref())?;
+        layers.extend(env_layers);
+
+        Ok(layers)
+    }
+
+    /// Messages for the unknown keys in the config of the given package.
+    ///
+    /// The same tables as in [`CliConfig::layers`] are checked.
+    pub fn unknown_keys(
+        metadata: &Metadata,
+        package: Option<&Package>,
+    ) -> anyhow::Result<Vec<String>> {
+        let current_dir =
+            env::current_dir().context("failed to determine the current directory")?;
+        let files = read_config_files(&current_dir, user_config_dir().as_deref())?;
+
+        Ok(loaded_tables(&files, metadata, package)
+            .iter()
+            .flat_map(LoadedTable::unknown_keys)
+            .collect())
+    }
+
+    /// The layers defined by `BEVY_CLI_*` environment variables, from general to specific.
+    ///
+    /// Each key can be set for all builds, e.g. `BEVY_CLI_FEATURES`, or only for a profile or
+    /// platform like the config tables, e.g. `BEVY_CLI_RELEASE_FEATURES`,
+    /// `BEVY_CLI_WEB_FEATURES` and `BEVY_CLI_WEB_RELEASE_FEATURES`.
+    /// Empty variables are ignored and relative paths are relative to the current directory.
+    fn env_layers(
+        var: impl Fn(&str) -> Option<String>,
+        current_dir: &Path,
+        is_web: bool,
+        is_release: bool,
+    ) -> anyhow::Result<Vec<ConfigLayer>> {
+        let profile = if is_release { "RELEASE_" } else { "DEV_" };
+        let platform = if is_web { "WEB_" } else { "NATIVE_" };
+        let mut layers = Vec::new();
+
+        for scope in [
+            String::new(),
+            profile.to_owned(),
+            platform.to_owned(),
+            format!("{platform}{profile}"),
+        ] {
+            for key in config_keys() {
+                let name = format!("BEVY_CLI_{scope}{}", key.to_uppercase().replace('-', "_"));
+                let Some(raw) = var(&name).filter(|raw| !raw.trim().is_empty()) else {
+                    continue;
+                };
+
+                // The value is parsed like a config table with a single key
+                let mut config = env_value(&key, &raw)
+                    .and_then(|value| {
+                        let table = Value::Object(Map::from_iter([(key.clone(), value)]));
+                        Self::from_specific_metadata(Some(&table))
+                    })
+                    .map_err(|error| anyhow::anyhow!("invalid value of `{name}`: {error}"))?;
+                config.resolve_paths(current_dir);
+
+                layers.push(ConfigLayer {
+                    source: name,
+                    config,
+                });
             }
-        }) else {
-            return Ok(Self::default());
+        }
+
+        Ok(layers)
+    }
+
+    /// The layers of the given tables, from general to specific.
+    ///
+    /// The presets are applied after all other layers, so that they take precedence.
+    /// The target sections are skipped if no target is given.
+    fn table_layers(
+        tables: &[LoadedTable<'_>],
+        is_web: bool,
+        is_release: bool,
+        preset: Option<&str>,
+        target: Option<&TargetCfg>,
+    ) -> anyhow::Result<Vec<ConfigLayer>> {
+        let mut layers = Vec::new();
+
+        for table in tables {
+            let mut table_layers =
+                Self::metadata_layers(table.table, table.values, is_web, is_release, target)?;
+            for layer in &mut table_layers {
+                layer.config.resolve_paths(table.root);
+            }
+            layers.append(&mut table_layers);
+        }
+
+        if let Some(preset) = preset {
+            let mut preset_layers = Vec::new();
+            for table in tables {
+                if let Some(mut layer) = Self::preset_layer(table, preset)? {
+                    layer.config.resolve_paths(table.root);
+                    preset_layers.push(layer);
+                }
+            }
+
+            if preset_layers.is_empty() {
+                let mut presets = BTreeSet::new();
+                for table in tables {
+                    if let Some(table_presets) = extract_presets(table)? {
+                        presets.extend(table_presets.keys().map(|name| format!("`{name}`")));
+                    }
+                }
+
+                if presets.is_empty() {
+                    bail!("unknown preset `{preset}`, no presets are defined in the config");
+                }
+                bail!(
+                    "unknown preset `{preset}`, the available presets are {}",
+                    presets.into_iter().collect::<Vec<_>>().join(", ")
+                );
+            }
+
+            layers.append(&mut preset_layers);
+        }
+
+        Ok(layers)
+    }
+
+    /// The layer of the given preset in the table, if the table defines the preset.
+    fn preset_layer(table: &LoadedTable<'_>, preset: &str) -> anyhow::Result<Option<ConfigLayer>> {
+        let Some(preset_config) = extract_presets(table)?.and_then(|presets| presets.get(preset))
+        else {
+            return Ok(None);
         };
 
-        let base_metadata = package_metadata.get("bevy_cli");
-        Self::merged_from_metadata(base_metadata, is_web, is_release)
+        let key = format!("preset.{preset}");
+        let config = Self::from_specific_metadata(Some(preset_config)).map_err(|error| {
+            anyhow::anyhow!("invalid config in {}: {error}", table.table.describe(&key))
+        })?;
+
+        Ok(Some(ConfigLayer {
+            source: table.table.name(&key),
+            config,
+        }))
+    }
+
+    /// Merge the layers into a single config, later layers take precedence.
+    fn merge_layers(layers: &[ConfigLayer]) -> Self {
+        layers.iter().fold(Self::default(), |config, layer| {
+            config.overwrite(&layer.config)
+        })
     }
 
-    /// Build a config from the `package.metadata.bevy_cli` table.
+    /// Make the relative paths in the config relative to the given directory.
+    fn resolve_paths(&mut self, root: &Path) {
+        if let Some(HttpsConfig::Certificate { cert, key }) = &mut self.https {
+            *cert = root.join(&*cert);
+            *key = root.join(&*key);
+        }
+
+        for path in &mut self.bundle_files {
+            *path = root.join(&*path);
+        }
+
+        if let Some(icon) = &mut self.app.icon {
+            *icon = root.join(&*icon);
+        }
+    }
+
+    /// Build a config from a `bevy_cli` metadata table, e.g. `package.metadata.bevy_cli`.
     ///
-    /// It is merged from the platform- and profile-specific configurations.
+    /// It is merged from the platform-, profile- and target-specific configurations.
+    #[cfg(test)]
     fn merged_from_metadata(
+        table: &str,
         cli_metadata: Option<&Value>,
         is_web: bool,
         is_release: bool,
+        target: Option<&TargetCfg>,
     ) -> anyhow::Result<Self> {
+        Ok(Self::merge_layers(&Self::metadata_layers(
+            ConfigTable::Metadata(table),
+            cli_metadata,
+            is_web,
+            is_release,
+            target,
+        )?))
+    }
+
+    /// Split a `bevy_cli` config table into the layers it is merged from.
+    ///
+    /// The base config comes first, followed by the profile-specific config, the
+    /// platform-specific config and the platform-profile combination.
+    /// The target sections matching the given target come last, the `cfg(...)` sections before
+    /// the section of the target triple.
+    /// The name of the table is used as source of the layers and in error messages.
+    fn metadata_layers(
+        table: ConfigTable<'_>,
+        cli_metadata: Option<&Value>,
+        is_web: bool,
+        is_release: bool,
+        target: Option<&TargetCfg>,
+    ) -> anyhow::Result<Vec<ConfigLayer>> {
         let profile = if is_release { "release" } else { "dev" };
         let platform = if is_web { "web" } else { "native" };
 
+        let target_sections = extract_target_sections(cli_metadata);
         let profile_metadata = cli_metadata.and_then(|metadata| metadata.get(profile));
         let platform_metadata = cli_metadata.and_then(|metadata| metadata.get(platform));
         let platform_profile_metadata =
             platform_metadata.and_then(|metadata| metadata.get(profile));
 
-        // Start with the base config
-        let config = Self::from_specific_metadata(cli_metadata)
-            .context("failed to parse package.metadata.bevy_cli")?
-            // Add the profile-specific config
-            .overwrite(
-                &Self::from_specific_metadata(profile_metadata).context(format!(
-                    "failed to parse package.metadata.bevy_cli.{profile}"
-                ))?,
-            )
-            // Then the platform-specific config
-            .overwrite(
-                &Self::from_specific_metadata(platform_metadata).context(format!(
-                    "failed to parse package.metadata.bevy_cli.{platform}"
-                ))?,
-            )
-            // Finally, the platform-profile combination
-            .overwrite(
-                &Self::from_specific_metadata(platform_profile_metadata).context(format!(
-                    "failed to parse package.metadata.bevy_cli.{platform}.{profile}"
-                ))?,
-            );
+        let mut sections = vec![
+            (String::new(), cli_metadata),
+            (profile.to_owned(), profile_metadata),
+            (platform.to_owned(), platform_metadata),
+            (format!("{platform}.{profile}"), platform_profile_metadata),
+        ];
+
+        if let (Some(target), Some(target_sections)) = (target, target_sections) {
+            let (cfg_sections, triple_sections): (Vec<_>, Vec<_>) = target_sections
+                .iter()
+                .partition(|(key, _)| key.starts_with("cfg("));
+
+            for (key, metadata) in cfg_sections.into_iter().chain(triple_sections) {
+                let matches = target.matches(key).map_err(|error| {
+                    anyhow::anyhow!(
+                        "invalid config in {}: {error:#}",
+                        table.describe("target-config")
+                    )
+                })?;
+                if matches {
+                    sections.push((format!("target-config.'{key}'"), Some(metadata)));
+                }
+            }
+        }
 
-        Ok(config)
+        sections
+            .into_iter()
+            .map(|(key, metadata)| {
+                let config = Self::from_specific_metadata(metadata).map_err(|error| {
+                    anyhow::anyhow!("invalid config in {}: {error}", table.describe(&key))
+                })?;
+                Ok(ConfigLayer {
+                    source: table.name(&key),
+                    config,
+                })
+            })
+            .collect()
     }
 
     /// Build a single config for a specific platform- or profile-specific configuration.
@@ -189,6 +715,18 @@ impl CliConfig {
             wasm_opt: extract_wasm_opt(metadata)?,
             web_multi_threading: extract_web_multi_threading(unstable_config)?,
             headers: extract_headers(metadata)?,
+            https: extract_https(metadata)?,
+            proxy: extract_proxy(metadata)?,
+            dynamic_linking: extract_dynamic_linking(metadata)?,
+            linker: extract_linker(metadata)?,
+            bundle_files: extract_bundle_files(metadata)?,
+            app: extract_app(metadata)?,
+            run_group: extract_run_group(metadata)?,
+            env: extract_env(metadata)?,
+            run_args: extract_run_args(metadata)?,
+            port: extract_port(metadata)?,
+            host: extract_host(metadata)?,
+            open: extract_open(metadata)?,
         })
     }
 
@@ -201,11 +739,26 @@ impl CliConfig {
             target: with.target.clone().or(self.target),
             default_features: with.default_features.or(self.default_features),
             wasm_opt: with.wasm_opt.clone().or(self.wasm_opt),
-            // Features, rustflags and headers are additive
+            // Features, rustflags, headers and bundle files are additive
             features: [self.features, with.features.clone()].concat(),
             rustflags: [self.rustflags, with.rustflags.clone()].concat(),
             headers: [self.headers, with.headers.clone()].concat(),
             web_multi_threading: with.web_multi_threading.or(self.web_multi_threading),
+            https: with.https.clone().or(self.https),
+            // Proxy rules are merged per path
+            proxy: self.proxy.into_iter().chain(with.proxy.clone()).collect(),
+            dynamic_linking: with.dynamic_linking.or(self.dynamic_linking),
+            linker: with.linker.or(self.linker),
+            bundle_files: [self.bundle_files, with.bundle_files.clone()].concat(),
+            app: self.app.overwrite(&with.app),
+            // The run group is replaced as a whole, so that processes can be removed from it
+            run_group: with.run_group.clone().or(self.run_group),
+            // Environment variables are merged per name, run arguments are additive
+            env: self.env.into_iter().chain(with.env.clone()).collect(),
+            run_args: [self.run_args, with.run_args.clone()].concat(),
+            port: with.port.or(self.port),
+            host: with.host.clone().or(self.host),
+            open: with.open.or(self.open),
         }
     }
 
@@ -232,6 +785,348 @@ impl CliConfig {
 
         Ok(())
     }
+
+    /// Append the rustflags to use the configured linker to the [`CliConfig`] rustflags.
+    ///
+    /// This must be called after [`CliConfig::append_cargo_config_rustflags`],
+    /// so that linker settings from the Cargo config are respected.
+    pub fn append_linker_rustflags(
+        &mut self,
+        target: Option<String>,
+        config: &cargo_config2::Config,
+    ) -> anyhow::Result<()> {
+        let Some(linker) = self.linker else {
+            return Ok(());
+        };
+
+        let target = match target {
+            Some(target) => target,
+            None => config.host_triple()?.to_owned(),
+        };
+
+        let configured_linker = config.linker(&*target)?;
+        let linker_rustflags = linker_rustflags(
+            linker,
+            &target,
+            configured_linker.as_deref(),
+            &self.rustflags,
+        )?;
+        self.rustflags.extend(linker_rustflags);
+
+        Ok(())
+    }
+}
+
+impl ConfigReport {
+    /// The source used for values passed on the command line.
+    const CLI_ARG: &str = "CLI argument";
+
+    /// Merge the layers and determine which of them the configured values come from.
+    ///
+    /// Values that are replaced as a whole only list the most specific source, while values
+    /// that are merged from multiple layers, like `features`, list all contributing sources.
+    pub fn from_layers(layers: &[ConfigLayer]) -> anyhow::Result<Self> {
+        let config = CliConfig::merge_layers(layers);
+        let merged = serde_json::to_value(&config)?;
+
+        let mut sources: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut last_values = BTreeMap::new();
+
+        for layer in layers {
+            let Value::Object(values) = serde_json::to_value(&layer.config)? else {
+                continue;
+            };
+
+            for (key, value) in values {
+                if let Some(value) = without_unset_values(value) {
+                    sources
+                        .entry(key.clone())
+                        .or_default()
+                        .push(layer.source.clone());
+                    last_values.insert(key, value);
+                }
+            }
+        }
+
+        for (key, key_sources) in &mut sources {
+            let merged_value = merged.get(key).cloned().and_then(without_unset_values);
+
+            if merged_value.as_ref() == last_values.get(key) {
+                key_sources.drain(..key_sources.len() - 1);
+            }
+        }
+
+        Ok(Self { config, sources })
+    }
+
+    /// The config after merging all sources.
+    pub fn config(&self) -> &CliConfig {
+        &self.config
+    }
+
+    /// Append the rustflags from the cargo config, see
+    /// [`CliConfig::append_cargo_config_rustflags`].
+    pub fn append_cargo_config_rustflags(
+        &mut self,
+        target: Option<String>,
+        config: &cargo_config2::Config,
+    ) -> anyhow::Result<()> {
+        let previous_length = self.config.rustflags.len();
+        self.config.append_cargo_config_rustflags(target, config)?;
+
+        if self.config.rustflags.len() > previous_length {
+            // The rustflags from the environment replace the ones from the config files
+            let source = if env::var_os("CARGO_ENCODED_RUSTFLAGS").is_some() {
+                "CARGO_ENCODED_RUSTFLAGS"
+            } else if env::var_os("RUSTFLAGS").is_some() {
+                "RUSTFLAGS"
+            } else {
+                ".cargo/config.toml"
+            };
+            self.add_source("rustflags", source);
+        }
+
+        Ok(())
+    }
+
+    /// Append the rustflags of the configured linker, see
+    /// [`CliConfig::append_linker_rustflags`].
+    pub fn append_linker_rustflags(
+        &mut self,
+        target: Option<String>,
+        config: &cargo_config2::Config,
+    ) -> anyhow::Result<()> {
+        let previous_length = self.config.rustflags.len();
+        self.config.append_linker_rustflags(target, config)?;
+
+        if self.config.rustflags.len() > previous_length {
+            self.add_source("rustflags", "linker");
+        }
+
+        Ok(())
+    }
+
+    /// Apply the values passed on the command line, in the same way as `bevy build` does.
+    pub fn apply_cli_args(
+        &mut self,
+        target: Option<&str>,
+        features: &[String],
+        no_default_features: bool,
+        rustflags: Option<&str>,
+    ) {
+        if let Some(target) = target {
+            self.config.target = Some(target.to_owned());
+            self.replace_source("target");
+        }
+
+        if !features.is_empty() {
+            self.config.features.extend(features.iter().cloned());
+            self.add_source("features", Self::CLI_ARG);
+        }
+
+        if no_default_features {
+            self.config.default_features = Some(false);
+            self.replace_source("default-features");
+        }
+
+        // The `--rustflags` argument replaces all other rustflags
+        if let Some(rustflags) = rustflags {
+            self.config.rustflags = vec![rustflags.to_owned()];
+            self.replace_source("rustflags");
+        }
+    }
+
+    /// Record that values of the key were added by the source.
+    fn add_source(&mut self, key: &str, source: &str) {
+        self.sources
+            .entry(key.to_owned())
+            .or_default()
+            .push(source.to_owned());
+    }
+
+    /// Record that the value of the key was replaced by a CLI argument.
+    fn replace_source(&mut self, key: &str) {
+        self.sources
+            .insert(key.to_owned(), vec![Self::CLI_ARG.to_owned()]);
+    }
+}
+
+impl Display for ConfigReport {
+    /// Print the configured values as TOML, each preceded by a comment with its sources.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Value::Object(values) =
+            serde_json::to_value(&self.config).map_err(|_| std::fmt::Error)?
+        else {
+            return Err(std::fmt::Error);
+        };
+
+        let mut entries: Vec<_> = values
+            .into_iter()
+            .filter_map(|(key, value)| {
+                Some((without_unset_values(value)?, self.sources.get(&key)?, key))
+            })
+            .collect();
+        // Tables must come last, otherwise the following keys would be part of them
+        entries.sort_by_key(|(value, _, _)| value.is_object());
+
+        let mut is_first = true;
+        for (value, sources, key) in entries {
+            if !is_first {
+                writeln!(f)?;
+            }
+            is_first = false;
+
+            let document =
+                toml::to_string(&BTreeMap::from([(key, value)])).map_err(|_| std::fmt::Error)?;
+            writeln!(f, "# {}", sources.join(", "))?;
+            write!(f, "{document}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove the values that are not set from a serialized config value.
+///
+/// Returns `None` if the value itself is not set, i.e. it is `null` or an empty array or table.
+fn without_unset_values(value: Value) -> Option<Value> {
+    match value {
+        Value::Null => None,
+        Value::Array(values) if values.is_empty() => None,
+        Value::Object(values) => {
+            let values: Map<String, Value> = values
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, without_unset_values(value)?)))
+                .collect();
+
+            (!values.is_empty()).then_some(Value::Object(values))
+        }
+        value => Some(value),
+    }
+}
+
+/// The config files of the Bevy CLI, from general to specific, together with the directory their
+/// paths are relative to.
+///
+/// The user config `config.toml` in the given directory comes first, followed by the project
+/// configs in the current directory and its parents, starting with the outermost one.
+/// A project config is either a `bevy.toml` or a `.bevy/config.toml` file.
+fn config_files(current_dir: &Path, user_config_dir: Option<&Path>) -> Vec<(PathBuf, PathBuf)> {
+    let mut files = Vec::new();
+
+    if let Some(user_config_dir) = user_config_dir {
+        let path = user_config_dir.join("config.toml");
+        if path.is_file() {
+            files.push((user_config_dir.to_owned(), path));
+        }
+    }
+
+    let mut project_files: Vec<_> = current_dir
+        .ancestors()
+        .filter_map(|directory| {
+            let nested_path = directory.join(".bevy").join("config.toml");
+            let path = directory.join("bevy.toml");
+
+            match (nested_path.is_file(), path.is_file()) {
+                (true, true) => {
+                    warn!(
+                        "both `{}` and `{}` exist, using `{}`",
+                        nested_path.display(),
+                        path.display(),
+                        nested_path.display()
+                    );
+                    Some((directory.to_owned(), nested_path))
+                }
+                (true, false) => Some((directory.to_owned(), nested_path)),
+                (false, true) => Some((directory.to_owned(), path)),
+                (false, false) => None,
+            }
+        })
+        .collect();
+    project_files.reverse();
+    files.append(&mut project_files);
+
+    files
+}
+
+/// Read and parse the config files, see [`config_files`].
+fn read_config_files(
+    current_dir: &Path,
+    user_config_dir: Option<&Path>,
+) -> anyhow::Result<Vec<ConfigFile>> {
+    config_files(current_dir, user_config_dir)
+        .into_iter()
+        .map(|(root, path)| {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let values = toml::from_str(&content)
+                .map_err(|error| anyhow::anyhow!("failed to parse {}: {error}", path.display()))?;
+
+            Ok(ConfigFile { path, root, values })
+        })
+        .collect()
+}
+
+/// The config tables of the given package, from general to specific.
+///
+/// The config files come first, followed by the `workspace.metadata.bevy_cli` table and the
+/// `package.metadata.bevy_cli` table.
+fn loaded_tables<'a>(
+    files: &'a [ConfigFile],
+    metadata: &'a Metadata,
+    package: Option<&'a Package>,
+) -> Vec<LoadedTable<'a>> {
+    let mut tables: Vec<_> = files.iter().map(ConfigFile::table).collect();
+    // Paths in the config are relative to the workspace or package root
+    tables.push(LoadedTable {
+        table: ConfigTable::Metadata("workspace.metadata.bevy_cli"),
+        values: metadata.workspace_metadata.get("bevy_cli"),
+        root: metadata.workspace_root.as_std_path(),
+    });
+    if let Some(package) = package {
+        tables.push(LoadedTable {
+            table: ConfigTable::Metadata("package.metadata.bevy_cli"),
+            values: package.metadata.get("bevy_cli"),
+            root: package
+                .manifest_path
+                .parent()
+                .map_or(Path::new(""), |root| root.as_std_path()),
+        });
+    }
+    tables
+}
+
+/// The directory of the user config, i.e. `~/.config/bevy`.
+///
+/// Respects `XDG_CONFIG_HOME` if it is set.
+fn user_config_dir() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::home_dir().map(|home| home.join(".config")))?;
+
+    Some(config_dir.join("bevy"))
+}
+
+/// Try to extract the presets defined in a config table.
+fn extract_presets<'t>(table: &LoadedTable<'t>) -> anyhow::Result<Option<&'t Map<String, Value>>> {
+    const KEY: &str = "preset";
+
+    match table.values.and_then(|values| values.get(KEY)) {
+        Some(Value::Object(presets)) => Ok(Some(presets)),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!(
+            "invalid config in {}: {KEY} must be a table of presets",
+            table.table.describe("")
+        ),
+    }
+}
+
+/// The target sections of a config table, e.g. `target-config.'cfg(windows)'`.
+///
+/// They are only defined in the base config.
+fn extract_target_sections(cli_metadata: Option<&Value>) -> Option<&Map<String, Value>> {
+    cli_metadata?.get("target-config")?.as_object()
 }
 
 /// Try to extract the target platform from a metadata map for the CLI.
@@ -357,6 +1252,243 @@ fn extract_wasm_opt(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<
     }
 }
 
+/// Try to extract the HTTPS configuration from a metadata map for the CLI.
+fn extract_https(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<HttpsConfig>> {
+    const KEY: &str = "https";
+
+    let Some(https) = cli_metadata.get(KEY) else {
+        return Ok(None);
+    };
+
+    match https {
+        Value::Bool(enabled) => Ok(Some(HttpsConfig::Enabled(*enabled))),
+        Value::Object(https) => {
+            let path = |name: &str| {
+                https
+                    .get(name)
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+                    .ok_or_else(|| anyhow::anyhow!("{KEY}.{name} must be a string"))
+            };
+
+            Ok(Some(HttpsConfig::Certificate {
+                cert: path("cert")?,
+                key: path("key")?,
+            }))
+        }
+        Value::Null => Ok(None),
+        _ => bail!("{KEY} must be a boolean or a table with `cert` and `key` paths"),
+    }
+}
+
+/// Try to extract the proxy rules of the web server from a metadata map for the CLI.
+fn extract_proxy(cli_metadata: &Map<String, Value>) -> anyhow::Result<BTreeMap<String, String>> {
+    const KEY: &str = "proxy";
+
+    let Some(proxy) = cli_metadata.get(KEY) else {
+        return Ok(BTreeMap::new());
+    };
+
+    match proxy {
+        Value::Object(proxy) => proxy
+            .iter()
+            .map(|(path, upstream)| {
+                upstream
+                    .as_str()
+                    .map(|upstream| (path.clone(), upstream.to_owned()))
+                    .ok_or_else(|| anyhow::anyhow!("{KEY}.\"{path}\" must be a URL string"))
+            })
+            .collect(),
+        Value::Null => Ok(BTreeMap::new()),
+        _ => bail!("{KEY} must be a table mapping paths to URLs"),
+    }
+}
+
+/// Try to extract whether Bevy should be linked dynamically from a metadata map for the CLI.
+fn extract_dynamic_linking(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<bool>> {
+    const KEY: &str = "dynamic-linking";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::Bool(dynamic_linking)) => Ok(Some(*dynamic_linking)),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY} must be a boolean"),
+    }
+}
+
+/// Try to extract the linker for native builds from a metadata map for the CLI.
+fn extract_linker(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<Linker>> {
+    const KEY: &str = "linker";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::String(linker)) => match linker.as_str() {
+            "auto" => Ok(Some(Linker::Auto)),
+            "mold" => Ok(Some(Linker::Mold)),
+            "lld" => Ok(Some(Linker::Lld)),
+            "default" => Ok(Some(Linker::Default)),
+            _ => bail!(r#"{KEY} must be one of "auto", "mold", "lld" or "default""#),
+        },
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY} must be a string"),
+    }
+}
+
+/// Try to extract the additional files of native bundles from a metadata map for the CLI.
+fn extract_bundle_files(cli_metadata: &Map<String, Value>) -> anyhow::Result<Vec<PathBuf>> {
+    const KEY: &str = "bundle-files";
+
+    let Some(bundle_files) = cli_metadata.get(KEY) else {
+        return Ok(Vec::new());
+    };
+
+    match bundle_files {
+        Value::Array(bundle_files) => bundle_files
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| anyhow::anyhow!("each bundle file must be a string"))
+            })
+            .collect(),
+        Value::Null => Ok(Vec::new()),
+        _ => bail!("{KEY} must be an array"),
+    }
+}
+
+/// Try to extract the metadata of the app used for packaging from a metadata map for the CLI.
+fn extract_app(cli_metadata: &Map<String, Value>) -> anyhow::Result<AppConfig> {
+    const KEY: &str = "app";
+
+    let app = match cli_metadata.get(KEY) {
+        Some(Value::Object(app)) => app,
+        Some(Value::Null) | None => return Ok(AppConfig::default()),
+        Some(_) => bail!("{KEY} must be a table"),
+    };
+
+    let string = |name: &str| match app.get(name) {
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY}.{name} must be a string"),
+    };
+
+    let categories = match app.get("categories") {
+        Some(Value::Array(categories)) => Some(
+            categories
+                .iter()
+                .map(|value| {
+                    value
+                        .as_str()
+                        .map(str::to_owned)
+                        .ok_or_else(|| anyhow::anyhow!("each category must be a string"))
+                })
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Some(Value::Null) | None => None,
+        Some(_) => bail!("{KEY}.categories must be an array"),
+    };
+
+    Ok(AppConfig {
+        display_name: string("display-name")?,
+        icon: string("icon")?.map(PathBuf::from),
+        categories,
+    })
+}
+
+/// Try to extract the binaries to run together from a metadata map for the CLI.
+fn extract_run_group(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<Vec<String>>> {
+    const KEY: &str = "run-group";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::Array(bins)) => bins
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(str::to_owned)
+                    .ok_or_else(|| anyhow::anyhow!("each binary of the {KEY} must be a string"))
+            })
+            .collect::<anyhow::Result<_>>()
+            .map(Some),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY} must be an array of binary names"),
+    }
+}
+
+/// Try to extract the environment variables of the app from a metadata map for the CLI.
+fn extract_env(cli_metadata: &Map<String, Value>) -> anyhow::Result<BTreeMap<String, String>> {
+    const KEY: &str = "env";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::Object(env)) => env
+            .iter()
+            .map(|(name, value)| {
+                value
+                    .as_str()
+                    .map(|value| (name.clone(), value.to_owned()))
+                    .ok_or_else(|| anyhow::anyhow!("{KEY}.{name} must be a string"))
+            })
+            .collect(),
+        Some(Value::Null) | None => Ok(BTreeMap::new()),
+        Some(_) => bail!("{KEY} must be a table mapping variable names to values"),
+    }
+}
+
+/// Try to extract the default arguments of the app from a metadata map for the CLI.
+fn extract_run_args(cli_metadata: &Map<String, Value>) -> anyhow::Result<Vec<String>> {
+    const KEY: &str = "run-args";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::Array(run_args)) => run_args
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(str::to_owned)
+                    .ok_or_else(|| anyhow::anyhow!("each argument of {KEY} must be a string"))
+            })
+            .collect(),
+        Some(Value::Null) | None => Ok(Vec::new()),
+        Some(_) => bail!("{KEY} must be an array"),
+    }
+}
+
+/// Try to extract the port of the web server from a metadata map for the CLI.
+fn extract_port(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<u16>> {
+    const KEY: &str = "port";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::Number(port)) => port
+            .as_u64()
+            .and_then(|port| u16::try_from(port).ok())
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("{KEY} must be a number between 0 and 65535")),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY} must be a number"),
+    }
+}
+
+/// Try to extract the host address of the web server from a metadata map for the CLI.
+fn extract_host(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<String>> {
+    const KEY: &str = "host";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::String(host)) => Ok(Some(host.clone())),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY} must be a string"),
+    }
+}
+
+/// Try to extract whether to open the app in the browser from a metadata map for the CLI.
+fn extract_open(cli_metadata: &Map<String, Value>) -> anyhow::Result<Option<bool>> {
+    const KEY: &str = "open";
+
+    match cli_metadata.get(KEY) {
+        Some(Value::Bool(open)) => Ok(Some(*open)),
+        Some(Value::Null) | None => Ok(None),
+        Some(_) => bail!("{KEY} must be a boolean"),
+    }
+}
+
 /// Try to extract the map containing unstable CLI features.
 fn extract_unstable_config(
     cli_metadata: &Map<String, Value>,
@@ -441,7 +1573,13 @@ mod tests {
             });
 
             assert_eq!(
-                CliConfig::merged_from_metadata(Some(&metadata), true, false)?,
+                CliConfig::merged_from_metadata(
+                    "package.metadata.bevy_cli",
+                    Some(&metadata),
+                    true,
+                    false,
+                    None,
+                )?,
                 CliConfig {
                     target: None,
                     features: vec![
@@ -458,7 +1596,19 @@ mod tests {
                     ],
                     wasm_opt: None,
                     web_multi_threading: None,
-                    headers: Vec::new()
+                    headers: Vec::new(),
+                    https: None,
+                    proxy: BTreeMap::new(),
+                    dynamic_linking: None,
+                    linker: None,
+                    bundle_files: Vec::new(),
+                    app: AppConfig::default(),
+                    run_group: None,
+                    env: BTreeMap::new(),
+                    run_args: Vec::new(),
+                    port: None,
+                    host: None,
+                    open: None,
                 }
             );
             Ok(())
@@ -488,7 +1638,13 @@ mod tests {
             });
 
             assert_eq!(
-                CliConfig::merged_from_metadata(Some(&metadata), false, true)?,
+                CliConfig::merged_from_metadata(
+                    "package.metadata.bevy_cli",
+                    Some(&metadata),
+                    false,
+                    true,
+                    None,
+                )?,
                 CliConfig {
                     target: None,
                     features: vec![
@@ -501,7 +1657,19 @@ mod tests {
                     rustflags: vec!["-C opt-level=2".to_owned(), "-C debuginfo=1".to_owned()],
                     wasm_opt: None,
                     web_multi_threading: None,
-                    headers: Vec::new()
+                    headers: Vec::new(),
+                    https: None,
+                    proxy: BTreeMap::new(),
+                    dynamic_linking: None,
+                    linker: None,
+                    bundle_files: Vec::new(),
+                    app: AppConfig::default(),
+                    run_group: None,
+                    env: BTreeMap::new(),
+                    run_args: Vec::new(),
+                    port: None,
+                    host: None,
+                    open: None,
                 }
             );
             Ok(())
@@ -529,7 +1697,13 @@ mod tests {
             });
 
             assert_eq!(
-                CliConfig::merged_from_metadata(Some(&metadata), false, false)?,
+                CliConfig::merged_from_metadata(
+                    "package.metadata.bevy_cli",
+                    Some(&metadata),
+                    false,
+                    false,
+                    None,
+                )?,
                 CliConfig {
                     target: None,
                     features: vec![
@@ -542,18 +1716,126 @@ mod tests {
                     rustflags: Vec::new(),
                     wasm_opt: None,
                     web_multi_threading: None,
-                    headers: Vec::new()
+                    headers: Vec::new(),
+                    https: None,
+                    proxy: BTreeMap::new(),
+                    dynamic_linking: None,
+                    linker: None,
+                    bundle_files: Vec::new(),
+                    app: AppConfig::default(),
+                    run_group: None,
+                    env: BTreeMap::new(),
+                    run_args: Vec::new(),
+                    port: None,
+                    host: None,
+                    open: None,
+                }
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn should_merge_env_and_run_args() -> anyhow::Result<()> {
+            let metadata = json!({
+                "env": { "RUST_LOG": "warn", "BEVY_ASSET_ROOT": "." },
+                "run-args": ["--windowed"],
+                "native": {
+                    "dev": {
+                        "env": { "RUST_LOG": "debug" },
+                        "run-args": ["--debug"],
+                    }
+                },
+                "web": {
+                    "env": { "RUST_LOG": "error" },
                 }
+            });
+
+            let config = CliConfig::merged_from_metadata(
+                "package.metadata.bevy_cli",
+                Some(&metadata),
+                false,
+                false,
+                None,
+            )?;
+            assert_eq!(
+                config.env(),
+                &BTreeMap::from([
+                    ("BEVY_ASSET_ROOT".to_owned(), ".".to_owned()),
+                    ("RUST_LOG".to_owned(), "debug".to_owned()),
+                ])
+            );
+            assert_eq!(config.run_args(), ["--windowed", "--debug"]);
+            Ok(())
+        }
+
+        #[test]
+        fn should_merge_matching_target_sections_last() -> anyhow::Result<()> {
+            let metadata = json!({
+                "features": ["wayland"],
+                "rustflags": ["-C", "debuginfo=1"],
+                "target": "x86_64-pc-windows-gnu",
+                "target-config": {
+                    "x86_64-pc-windows-gnu": { "rustflags": ["-C", "link-arg=-static"] },
+                    "cfg(windows)": { "default-features": false, "rustflags": ["-C", "debuginfo=0"] },
+                    "cfg(unix)": { "features": ["x11"] },
+                    "x86_64-pc-windows-msvc": { "features": ["msvc"] },
+                },
+                "native": { "dev": { "features": ["dev"] } },
+            });
+            let target = TargetCfg::from_rustc_output(
+                "x86_64-pc-windows-gnu".to_owned(),
+                "target_os=\"windows\"\nwindows\n",
+            );
+
+            let config = CliConfig::merged_from_metadata(
+                "package.metadata.bevy_cli",
+                Some(&metadata),
+                false,
+                false,
+                Some(&target),
+            )?;
+            assert_eq!(config.target(), Some("x86_64-pc-windows-gnu"));
+            assert_eq!(config.features(), ["wayland", "dev"]);
+            assert!(!config.default_features());
+            assert_eq!(
+                config.rustflags().as_deref(),
+                Some("-C debuginfo=1 -C debuginfo=0 -C link-arg=-static")
             );
             Ok(())
         }
 
+        #[test]
+        fn should_report_invalid_target_sections() {
+            let metadata = json!({ "target-config": { "cfg(unix windows)": {} } });
+            let target =
+                TargetCfg::from_rustc_output("x86_64-unknown-linux-gnu".to_owned(), "unix");
+
+            let error = CliConfig::merged_from_metadata(
+                "package.metadata.bevy_cli",
+                Some(&metadata),
+                false,
+                false,
+                Some(&target),
+            )
+            .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "invalid config in `package.metadata.bevy_cli.target-config`: invalid target `cfg(unix windows)`: unexpected `w`"
+            );
+        }
+
         #[test]
         fn should_not_require_any_config() -> anyhow::Result<()> {
             let metadata = json!({});
 
             assert_eq!(
-                CliConfig::merged_from_metadata(Some(&metadata), true, false)?,
+                CliConfig::merged_from_metadata(
+                    "package.metadata.bevy_cli",
+                    Some(&metadata),
+                    true,
+                    false,
+                    None,
+                )?,
                 CliConfig::default()
             );
             Ok(())
@@ -580,7 +1862,13 @@ mod tests {
             });
 
             assert_eq!(
-                CliConfig::merged_from_metadata(Some(&metadata), false, true)?,
+                CliConfig::merged_from_metadata(
+                    "package.metadata.bevy_cli",
+                    Some(&metadata),
+                    false,
+                    true,
+                    None,
+                )?,
                 CliConfig {
                     target: None,
                     features: vec!["base".to_owned(),],
@@ -588,13 +1876,366 @@ mod tests {
                     rustflags: Vec::new(),
                     wasm_opt: None,
                     web_multi_threading: None,
-                    headers: Vec::new()
+                    headers: Vec::new(),
+                    https: None,
+                    proxy: BTreeMap::new(),
+                    dynamic_linking: None,
+                    linker: None,
+                    bundle_files: Vec::new(),
+                    app: AppConfig::default(),
+                    run_group: None,
+                    env: BTreeMap::new(),
+                    run_args: Vec::new(),
+                    port: None,
+                    host: None,
+                    open: None,
                 }
             );
             Ok(())
         }
     }
 
+    mod file_layers {
+        use std::fs;
+
+        use super::*;
+
+        /// The layers of the config files for web dev builds.
+        fn file_layers(
+            current_dir: &Path,
+            user_config_dir: Option<&Path>,
+            preset: Option<&str>,
+        ) -> anyhow::Result<Vec<ConfigLayer>> {
+            let files = read_config_files(current_dir, user_config_dir)?;
+            let tables: Vec<_> = files.iter().map(ConfigFile::table).collect();
+            CliConfig::table_layers(&tables, true, false, preset, None)
+        }
+
+        #[test]
+        fn should_merge_config_files_from_general_to_specific() -> anyhow::Result<()> {
+            let dir = tempfile::tempdir()?;
+            let user_config_dir = dir.path().join("user");
+            let project_dir = dir.path().join("project");
+            let current_dir = project_dir.join("game");
+
+            fs::create_dir_all(&user_config_dir)?;
+            fs::create_dir_all(project_dir.join(".bevy"))?;
+            fs::create_dir_all(&current_dir)?;
+            fs::write(
+                user_config_dir.join("config.toml"),
+                r#"
+                features = ["user"]
+                linker = "mold"
+                port = 8080
+                open = true
+                "#,
+            )?;
+            fs::write(
+                project_dir.join(".bevy/config.toml"),
+                r#"
+                features = ["project"]
+                bundle-files = ["LICENSE"]
+
+                [web]
+                port = 3000
+                "#,
+            )?;
+            fs::write(current_dir.join("bevy.toml"), r#"features = ["game"]"#)?;
+
+            let layers = file_layers(&current_dir, Some(&user_config_dir), None)?;
+            let config = CliConfig::merge_layers(&layers);
+
+            assert_eq!(config.features(), ["user", "project", "game"]);
+            assert_eq!(config.linker, Some(Linker::Mold));
+            assert_eq!(config.port, Some(3000));
+            assert_eq!(config.open, Some(true));
+            assert_eq!(config.bundle_files(), [project_dir.join("LICENSE")]);
+            assert_eq!(
+                layers[6].source,
+                format!("web in {}", project_dir.join(".bevy/config.toml").display())
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn should_report_unknown_keys_in_config_files() -> anyhow::Result<()> {
+            let dir = tempfile::tempdir()?;
+            fs::write(dir.path().join("bevy.toml"), "[web]\nports = 3000")?;
+
+            let files = read_config_files(dir.path(), None)?;
+            let unknown_keys = files[0].table().unknown_keys();
+
+            assert_eq!(
+                unknown_keys,
+                [format!(
+                    "unknown key `web.ports` in `{}`, did you mean `port`?",
+                    dir.path().join("bevy.toml").display()
+                )]
+            );
+            // Unknown keys are only reported, they don't prevent using the rest of the config
+            assert!(file_layers(dir.path(), None, None).is_ok());
+            Ok(())
+        }
+    }
+
+    mod env_layers {
+        use super::*;
+
+        #[test]
+        fn should_override_config_with_env_variables() -> anyhow::Result<()> {
+            let vars = BTreeMap::from([
+                ("BEVY_CLI_FEATURES", "ci"),
+                ("BEVY_CLI_TARGET", ""),
+                ("BEVY_CLI_WASM_OPT", "false"),
+                (
+                    "BEVY_CLI_WEB_HEADERS",
+                    r#"["Cross-Origin-Opener-Policy: same-origin"]"#,
+                ),
+                ("BEVY_CLI_NATIVE_FEATURES", "native"),
+                ("BEVY_CLI_WEB_RELEASE_BUNDLE_FILES", "LICENSE"),
+                ("BEVY_CLI_CARGO", "cargo"),
+            ]);
+            let var = |name: &str| vars.get(name).map(|value| (*value).to_owned());
+
+            let layers = CliConfig::env_layers(var, Path::new("/project"), true, true)?;
+            assert_eq!(
+                layers
+                    .iter()
+                    .map(|layer| layer.source.as_str())
+                    .collect::<Vec<_>>(),
+                [
+                    "BEVY_CLI_FEATURES",
+                    "BEVY_CLI_WASM_OPT",
+                    "BEVY_CLI_WEB_HEADERS",
+                    "BEVY_CLI_WEB_RELEASE_BUNDLE_FILES"
+                ]
+            );
+
+            let config = CliConfig::merge_layers(&layers);
+            assert_eq!(config.features(), ["ci"]);
+            assert_eq!(config.target(), None);
+            assert_eq!(config.wasm_opt, Some(ExternalCliArgs::Enabled(false)));
+            assert_eq!(config.bundle_files(), [Path::new("/project/LICENSE")]);
+            Ok(())
+        }
+
+        #[test]
+        fn should_name_invalid_env_variable() {
+            let var = |name: &str| (name == "BEVY_CLI_PORT").then(|| "http".to_owned());
+
+            assert_eq!(
+                CliConfig::env_layers(var, Path::new("/project"), false, false)
+                    .unwrap_err()
+                    .to_string(),
+                "invalid value of `BEVY_CLI_PORT`: `http` is not a number"
+            );
+        }
+    }
+
+    mod presets {
+        use std::fs;
+
+        use super::*;
+
+        fn write_config(dir: &Path) -> anyhow::Result<()> {
+            fs::write(
+                dir.join("bevy.toml"),
+                r#"
+                features = ["dev_tools"]
+                target = "x86_64-unknown-linux-gnu"
+
+                [web]
+                features = ["web"]
+
+                [preset.profiling]
+                features = ["bevy/trace_tracy"]
+                rustflags = ["-Cdebuginfo=2"]
+
+                [preset.demo]
+                target = "aarch64-unknown-linux-gnu"
+                "#,
+            )?;
+            Ok(())
+        }
+
+        fn preset_config(dir: &Path, preset: &str) -> anyhow::Result<CliConfig> {
+            let files = read_config_files(dir, None)?;
+            let tables: Vec<_> = files.iter().map(ConfigFile::table).collect();
+            let layers = CliConfig::table_layers(&tables, true, false, Some(preset), None)?;
+            Ok(CliConfig::merge_layers(&layers))
+        }
+
+        #[test]
+        fn should_apply_preset_last() -> anyhow::Result<()> {
+            let dir = tempfile::tempdir()?;
+            write_config(dir.path())?;
+
+            let config = preset_config(dir.path(), "profiling")?;
+            assert_eq!(config.features(), ["dev_tools", "web", "bevy/trace_tracy"]);
+            assert_eq!(config.rustflags(), Some("-Cdebuginfo=2".to_owned()));
+
+            let config = preset_config(dir.path(), "demo")?;
+            assert_eq!(config.target(), Some("aarch64-unknown-linux-gnu"));
+            Ok(())
+        }
+
+        #[test]
+        fn should_list_available_presets_for_unknown_preset() -> anyhow::Result<()> {
+            let dir = tempfile::tempdir()?;
+            write_config(dir.path())?;
+
+            assert_eq!(
+                preset_config(dir.path(), "steam").unwrap_err().to_string(),
+                "unknown preset `steam`, the available presets are `demo`, `profiling`"
+            );
+            Ok(())
+        }
+    }
+
+    mod config_report {
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn should_list_sources_of_values() -> anyhow::Result<()> {
+            let workspace_metadata = json!({
+                "features": ["shared"],
+                "target": "x86_64-unknown-linux-gnu",
+                "env": { "RUST_LOG": "info" },
+            });
+            let package_metadata = json!({
+                "features": ["game"],
+                "web": {
+                    "target": "wasm32-unknown-unknown",
+                    "env": { "RUST_LOG": "debug" },
+                },
+            });
+
+            let layers = [
+                CliConfig::metadata_layers(
+                    ConfigTable::Metadata("workspace.metadata.bevy_cli"),
+                    Some(&workspace_metadata),
+                    true,
+                    false,
+                    None,
+                )?,
+                CliConfig::metadata_layers(
+                    ConfigTable::Metadata("package.metadata.bevy_cli"),
+                    Some(&package_metadata),
+                    true,
+                    false,
+                    None,
+                )?,
+            ]
+            .concat();
+            let mut report = ConfigReport::from_layers(&layers)?;
+            report.apply_cli_args(None, &["cli".to_owned()], true, None);
+
+            assert_eq!(
+                report.sources,
+                BTreeMap::from([
+                    (
+                        "default-features".to_owned(),
+                        vec!["CLI argument".to_owned()]
+                    ),
+                    (
+                        "env".to_owned(),
+                        vec!["package.metadata.bevy_cli.web".to_owned()]
+                    ),
+                    (
+                        "features".to_owned(),
+                        vec![
+                            "workspace.metadata.bevy_cli".to_owned(),
+                            "package.metadata.bevy_cli".to_owned(),
+                            "CLI argument".to_owned()
+                        ]
+                    ),
+                    (
+                        "target".to_owned(),
+                        vec!["package.metadata.bevy_cli.web".to_owned()]
+                    ),
+                ])
+            );
+            assert_eq!(report.config.features(), ["shared", "game", "cli"]);
+            assert_eq!(
+                report.to_string(),
+                r#"# CLI argument
+default-features = false
+
+# workspace.metadata.bevy_cli, package.metadata.bevy_cli, CLI argument
+features = ["shared", "game", "cli"]
+
+# package.metadata.bevy_cli.web
+target = "wasm32-unknown-unknown"
+
+# package.metadata.bevy_cli.web
+[env]
+RUST_LOG = "debug"
+"#
+            );
+            Ok(())
+        }
+    }
+
+    mod resolve {
+        use std::fs;
+
+        use super::*;
+
+        #[test]
+        fn should_use_workspace_config_as_base() -> anyhow::Result<()> {
+            let dir = tempfile::tempdir()?;
+            fs::write(
+                dir.path().join("Cargo.toml"),
+                r#"
+                [workspace]
+                members = ["game"]
+
+                [workspace.metadata.bevy_cli]
+                features = ["shared"]
+                target = "x86_64-unknown-linux-gnu"
+                bundle-files = ["LICENSE"]
+                "#,
+            )?;
+            fs::create_dir_all(dir.path().join("game/src"))?;
+            fs::write(dir.path().join("game/src/main.rs"), "fn main() {}")?;
+            fs::write(
+                dir.path().join("game/Cargo.toml"),
+                r#"
+                [package]
+                name = "game"
+                version = "0.1.0"
+                edition = "2024"
+
+                [package.metadata.bevy_cli]
+                features = ["game"]
+                target = "aarch64-unknown-linux-gnu"
+                "#,
+            )?;
+
+            let metadata = cargo_metadata::MetadataCommand::new()
+                .current_dir(dir.path())
+                .other_options(vec!["--offline".to_owned()])
+                .exec()?;
+            let package = metadata.workspace_packages()[0];
+
+            let workspace_config = CliConfig::resolve(&metadata, None, false, false, None, None)?;
+            assert_eq!(workspace_config.features(), ["shared"]);
+            assert_eq!(workspace_config.target(), Some("x86_64-unknown-linux-gnu"));
+            assert_eq!(
+                workspace_config.bundle_files(),
+                [metadata.workspace_root.join("LICENSE").into_std_path_buf()]
+            );
+
+            let package_config =
+                CliConfig::resolve(&metadata, Some(package), false, false, None, None)?;
+            assert_eq!(package_config.features(), ["shared", "game"]);
+            assert_eq!(package_config.target(), Some("aarch64-unknown-linux-gnu"));
+            Ok(())
+        }
+    }
+
     mod extract_target {
         use serde_json::Map;
 
@@ -714,6 +2355,145 @@ mod tests {
         }
     }
 
+    mod extract_https {
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn should_return_enabled_if_https_is_true() -> anyhow::Result<()> {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("https".to_owned(), true.into());
+            assert_eq!(
+                extract_https(&cli_metadata)?,
+                Some(HttpsConfig::Enabled(true))
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn should_return_certificate_if_https_is_table() -> anyhow::Result<()> {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert(
+                "https".to_owned(),
+                json!({ "cert": "certs/cert.pem", "key": "certs/key.pem" }),
+            );
+            assert_eq!(
+                extract_https(&cli_metadata)?,
+                Some(HttpsConfig::Certificate {
+                    cert: PathBuf::from("certs/cert.pem"),
+                    key: PathBuf::from("certs/key.pem"),
+                })
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn should_return_error_if_key_is_missing() {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("https".to_owned(), json!({ "cert": "certs/cert.pem" }));
+            assert!(extract_https(&cli_metadata).is_err());
+        }
+    }
+
+    mod extract_proxy {
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn should_return_proxy_rules_if_table() -> anyhow::Result<()> {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert(
+                "proxy".to_owned(),
+                json!({ "/api": "http://127.0.0.1:8080" }),
+            );
+            assert_eq!(
+                extract_proxy(&cli_metadata)?,
+                BTreeMap::from([("/api".to_owned(), "http://127.0.0.1:8080".to_owned())])
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn should_return_error_if_url_is_not_a_string() {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("proxy".to_owned(), json!({ "/api": 8080 }));
+            assert!(extract_proxy(&cli_metadata).is_err());
+        }
+    }
+
+    mod extract_app {
+        use serde_json::json;
+
+        use super::*;
+
+        #[test]
+        fn should_return_app_metadata_if_table() -> anyhow::Result<()> {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert(
+                "app".to_owned(),
+                json!({
+                    "display-name": "My Game",
+                    "icon": "assets/icon.png",
+                    "categories": ["Game", "ActionGame"],
+                }),
+            );
+            assert_eq!(
+                extract_app(&cli_metadata)?,
+                AppConfig {
+                    display_name: Some("My Game".to_owned()),
+                    icon: Some(PathBuf::from("assets/icon.png")),
+                    categories: Some(vec!["Game".to_owned(), "ActionGame".to_owned()]),
+                }
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn should_return_error_if_categories_is_not_an_array() {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("app".to_owned(), json!({ "categories": "Game" }));
+            assert!(extract_app(&cli_metadata).is_err());
+        }
+    }
+
+    mod extract_dynamic_linking {
+        use super::*;
+
+        #[test]
+        fn should_return_none_if_not_specified() -> anyhow::Result<()> {
+            assert_eq!(extract_dynamic_linking(&Map::new())?, None);
+            Ok(())
+        }
+
+        #[test]
+        fn should_return_error_if_not_a_boolean() {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("dynamic-linking".to_owned(), "yes".into());
+            assert!(extract_dynamic_linking(&cli_metadata).is_err());
+        }
+    }
+
+    mod extract_linker {
+        use super::*;
+
+        #[test]
+        fn should_return_linker_if_specified() -> anyhow::Result<()> {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("linker".to_owned(), "mold".into());
+            assert_eq!(extract_linker(&cli_metadata)?, Some(Linker::Mold));
+            Ok(())
+        }
+
+        #[test]
+        fn should_return_error_if_linker_is_unknown() {
+            let mut cli_metadata = Map::new();
+            cli_metadata.insert("linker".to_owned(), "gold".into());
+            assert!(extract_linker(&cli_metadata).is_err());
+        }
+    }
+
     mod extract_wasm_opt {
         use super::*;
 
@@ -872,7 +2652,13 @@ mod tests {
                     "rustflags": ["-C debuginfo=1"]
                 },
             });
-            let mut cli_config = CliConfig::merged_from_metadata(Some(&metadata), false, false)?;
+            let mut cli_config = CliConfig::merged_from_metadata(
+                "package.metadata.bevy_cli",
+                Some(&metadata),
+                false,
+                false,
+                None,
+            )?;
             let cargo_config = cargo_config()?;
 
             cli_config.append_cargo_config_rustflags(None, &cargo_config)?;
@@ -896,7 +2682,13 @@ mod tests {
                     "rustflags": ["-C debuginfo=1"]
                 },
             });
-            let mut cli_config = CliConfig::merged_from_metadata(Some(&metadata), true, false)?;
+            let mut cli_config = CliConfig::merged_from_metadata(
+                "package.metadata.bevy_cli",
+                Some(&metadata),
+                true,
+                false,
+                None,
+            )?;
             let cargo_config = cargo_config()?;
 
             cli_config.append_cargo_config_rustflags(
@@ -0,0 +1,488 @@
+//! The JSON Schema of the `bevy_cli` config table, used to validate the keys of the config, to
+//! parse config values from environment variables and exported by `bevy config schema` for editors.
+
+use anyhow::bail;
+use serde_json::{Map, Value, json};
+
+/// The minimum similarity of an unknown key to a known key to suggest it.
+const SUGGESTION_SIMILARITY: f64 = 0.8;
+
+/// The JSON Schema of the `package.metadata.bevy_cli` and `workspace.metadata.bevy_cli` tables.
+pub(crate) fn config_schema() -> Value {
+    // The target sections are matched against the target, so they can't change it
+    let mut target_properties = config_properties();
+    target_properties.remove("target");
+
+    let mut platform_properties = config_properties();
+    for profile in ["dev", "release"] {
+        platform_properties.insert(
+            profile.to_owned(),
+            json!({
+                "description": format!("The config for `{profile}` builds of the platform."),
+                "$ref": "#/definitions/profile",
+            }),
+        );
+    }
+
+    let mut root_properties = config_properties();
+    for profile in ["dev", "release"] {
+        root_properties.insert(
+            profile.to_owned(),
+            json!({
+                "description": format!("The config for `{profile}` builds."),
+                "$ref": "#/definitions/profile",
+            }),
+        );
+    }
+    for platform in ["native", "web"] {
+        root_properties.insert(
+            platform.to_owned(),
+            json!({
+                "description": format!("The config for {platform} builds."),
+                "$ref": "#/definitions/platform",
+            }),
+        );
+    }
+    root_properties.insert(
+        "target-config".to_owned(),
+        json!({
+            "description": "Sections that only apply to specific targets, named by a target triple or a `cfg(...)` expression, e.g. `x86_64-pc-windows-gnu` or `cfg(windows)`.",
+            "type": "object",
+            "additionalProperties": { "$ref": "#/definitions/target" },
+        }),
+    );
+    root_properties.insert(
+        "preset".to_owned(),
+        json!({
+            "description": "Named presets, which are applied on top of the other config when selected with `--preset <NAME>`.",
+            "type": "object",
+            "additionalProperties": { "$ref": "#/definitions/profile" },
+        }),
+    );
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Bevy CLI config",
+        "description": "The config of the Bevy CLI, defined in `package.metadata.bevy_cli` or `workspace.metadata.bevy_cli`.",
+        "type": "object",
+        "properties": root_properties,
+        "additionalProperties": false,
+        "definitions": {
+            "platform": {
+                "type": "object",
+                "properties": platform_properties,
+                "additionalProperties": false,
+            },
+            "profile": {
+                "type": "object",
+                "properties": config_properties(),
+                "additionalProperties": false,
+            },
+            "target": {
+                "type": "object",
+                "properties": target_properties,
+                "additionalProperties": false,
+            },
+        },
+    })
+}
+
+/// The keys that can be configured in every table of the config.
+fn config_properties() -> Map<String, Value> {
+    let string_array = |description: &str| {
+        json!({
+            "description": description,
+            "type": "array",
+            "items": { "type": "string" },
+        })
+    };
+    let string_map = |description: &str| {
+        json!({
+            "description": description,
+            "type": "object",
+            "additionalProperties": { "type": "string" },
+        })
+    };
+
+    let properties = json!({
+        "target": {
+            "description": "The platform to target with the build.",
+            "type": "string",
+        },
+        "features": string_array("Additional features that should be enabled."),
+        "default-features": {
+            "description": "Whether to use the default features.",
+            "type": "boolean",
+        },
+        "default_features": {
+            "description": "Deprecated, use `default-features` instead.",
+            "type": "boolean",
+            "deprecated": true,
+        },
+        "headers": string_array(
+            "Additional HTTP headers to send with requests, in the format `KEY:VALUE` or `KEY=VALUE`."
+        ),
+        "rustflags": {
+            "description": "Additional flags for `rustc`, as array or space-separated string.",
+            "anyOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } },
+            ],
+        },
+        "wasm-opt": {
+            "description": "Use `wasm-opt` to optimize wasm binaries, optionally with custom arguments.",
+            "anyOf": [
+                { "type": "boolean" },
+                { "type": "array", "items": { "type": "string" } },
+            ],
+        },
+        "https": {
+            "description": "Serve the app over HTTPS, optionally with a custom certificate.",
+            "anyOf": [
+                { "type": "boolean" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "cert": {
+                            "description": "The path of the certificate in the PEM format.",
+                            "type": "string",
+                        },
+                        "key": {
+                            "description": "The path of the private key in the PEM format.",
+                            "type": "string",
+                        },
+                    },
+                    "required": ["cert", "key"],
+                    "additionalProperties": false,
+                },
+            ],
+        },
+        "proxy": string_map("Forward requests with the given path prefixes to other servers."),
+        "dynamic-linking": {
+            "description": "Link Bevy dynamically in native dev builds.",
+            "type": "boolean",
+        },
+        "linker": {
+            "description": "The linker to use for native builds.",
+            "enum": ["auto", "mold", "lld", "default"],
+        },
+        "bundle-files": string_array("Additional files to copy into native bundles."),
+        "app": {
+            "description": "Metadata of the app used for packaging.",
+            "type": "object",
+            "properties": {
+                "display-name": {
+                    "description": "The human-readable name of the app.",
+                    "type": "string",
+                },
+                "icon": {
+                    "description": "The icon of the app, as PNG or SVG.",
+                    "type": "string",
+                },
+                "categories": {
+                    "description": "The categories of the app, as defined by the freedesktop.org menu specification.",
+                    "type": "array",
+                    "items": { "type": "string" },
+                },
+            },
+            "additionalProperties": false,
+        },
+        "run-group": string_array(
+            "The binaries to run together if no binary is passed to `bevy run`."
+        ),
+        "env": string_map("Environment variables to set for the app started by `bevy run`."),
+        "run-args": string_array("The default arguments passed to the app started by `bevy run`."),
+        "port": {
+            "description": "The port to run the web server on.",
+            "type": "integer",
+            "minimum": 0,
+            "maximum": 65535,
+        },
+        "host": {
+            "description": "The host address to run the web server on.",
+            "type": "string",
+        },
+        "open": {
+            "description": "Open the app in the browser.",
+            "type": "boolean",
+        },
+        "unstable": {
+            "description": "Experimental features, which might change or be removed.",
+            "type": "object",
+            "properties": {
+                "web-multi-threading": {
+                    "description": "Enable building and running apps that use Wasm multi-threading features.",
+                    "type": "boolean",
+                },
+            },
+            "additionalProperties": false,
+        },
+    });
+
+    let Value::Object(properties) = properties else {
+        unreachable!("the properties are defined as object");
+    };
+    properties
+}
+
+/// The keys that can be configured in every table of the config, except for deprecated ones.
+pub(crate) fn config_keys() -> Vec<String> {
+    config_properties()
+        .into_iter()
+        .filter(|(_, property)| property.get("deprecated") != Some(&Value::Bool(true)))
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// Parse the value of an environment variable for the given config key, based on its type.
+///
+/// Booleans, numbers and strings are used as they are. Arrays can either be TOML arrays or
+/// whitespace-separated lists, like `RUSTFLAGS`. Tables must be TOML inline tables.
+pub(crate) fn env_value(key: &str, raw: &str) -> anyhow::Result<Value> {
+    let properties = config_properties();
+    let Some(property) = properties.get(key) else {
+        bail!("unknown key `{key}`");
+    };
+
+    let types: Vec<&str> = match property.get("anyOf") {
+        Some(Value::Array(variants)) => variants
+            .iter()
+            .filter_map(|variant| variant.get("type")?.as_str())
+            .collect(),
+        _ => property
+            .get("type")
+            .and_then(Value::as_str)
+            .into_iter()
+            .collect(),
+    };
+    let raw = raw.trim();
+
+    if types.contains(&"boolean")
+        && let Ok(value) = raw.parse::<bool>()
+    {
+        Ok(Value::Bool(value))
+    } else if types.contains(&"integer") {
+        raw.parse::<u64>()
+            .map(Value::from)
+            .map_err(|_| anyhow::anyhow!("`{raw}` is not a number"))
+    } else if types.contains(&"object") || (types.contains(&"array") && raw.starts_with('[')) {
+        toml_value(raw)
+    } else if types.contains(&"string") || types.is_empty() {
+        // Enums like the `linker` don't define a type
+        Ok(Value::String(raw.to_owned()))
+    } else if types.contains(&"array") {
+        Ok(raw
+            .split_whitespace()
+            .map(|item| Value::String(item.to_owned()))
+            .collect())
+    } else {
+        bail!("`{raw}` must be `true` or `false`")
+    }
+}
+
+/// Parse a TOML value, e.g. an array or inline table.
+fn toml_value(raw: &str) -> anyhow::Result<Value> {
+    let Ok(mut document) = toml::from_str::<Map<String, Value>>(&format!("value = {raw}")) else {
+        bail!("`{raw}` is not a valid TOML value");
+    };
+    Ok(document.remove("value").unwrap_or_default())
+}
+
+/// A key of a config table that is not part of the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnknownKey {
+    /// The path of the key inside of the table, e.g. `web.wasm-opts`.
+    pub key: String,
+    /// A similar key that is part of the schema.
+    pub suggestion: Option<String>,
+}
+
+/// Find the keys of the config table that are not part of the schema.
+pub(crate) fn unknown_keys(config: &Value) -> Vec<UnknownKey> {
+    let schema = config_schema();
+    let mut unknown_keys = Vec::new();
+    collect_unknown_keys(&schema, &schema, None, config, &mut unknown_keys);
+    unknown_keys
+}
+
+/// Recursively check the keys of the value against the schema.
+fn collect_unknown_keys(
+    root: &Value,
+    schema: &Value,
+    path: Option<&str>,
+    value: &Value,
+    unknown_keys: &mut Vec<UnknownKey>,
+) {
+    let schema = resolve_reference(root, schema);
+    let Value::Object(values) = value else {
+        return;
+    };
+
+    // Check the table variant of values like `https`, which can also be a boolean
+    if let Some(Value::Array(variants)) = schema.get("anyOf") {
+        if let Some(variant) = variants
+            .iter()
+            .find(|variant| variant.get("type") == Some(&json!("object")))
+        {
+            collect_unknown_keys(root, variant, path, value, unknown_keys);
+        }
+        return;
+    }
+
+    let properties = match schema.get("properties") {
+        Some(Value::Object(properties)) => properties,
+        _ => &Map::new(),
+    };
+    let additional_properties = schema.get("additionalProperties");
+
+    for (key, value) in values {
+        let key_path = match path {
+            Some(path) => format!("{path}.{key}"),
+            None => key.clone(),
+        };
+
+        if let Some(property) = properties.get(key) {
+            collect_unknown_keys(root, property, Some(&key_path), value, unknown_keys);
+        } else if let Some(additional_property @ Value::Object(_)) = additional_properties {
+            // Tables with arbitrary keys, e.g. the presets
+            collect_unknown_keys(
+                root,
+                additional_property,
+                Some(&key_path),
+                value,
+                unknown_keys,
+            );
+        } else if additional_properties == Some(&Value::Bool(false)) {
+            let suggestion = properties
+                .keys()
+                .filter(|known_key| *known_key != "default_features")
+                .map(|known_key| (strsim::jaro_winkler(key, known_key), known_key))
+                .filter(|(similarity, _)| *similarity >= SUGGESTION_SIMILARITY)
+                .max_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, known_key)| known_key.clone());
+
+            unknown_keys.push(UnknownKey {
+                key: key_path,
+                suggestion,
+            });
+        }
+    }
+}
+
+/// Follow a `$ref` to the definitions of the schema.
+fn resolve_reference<'s>(root: &'s Value, schema: &'s Value) -> &'s Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/definitions/"))
+        .and_then(|name| root.get("definitions")?.get(name))
+        .unwrap_or(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CliConfig;
+
+    #[test]
+    fn should_suggest_similar_keys() {
+        let config = json!({
+            "default_feature": false,
+            "features": ["base"],
+            "web": {
+                "wasm-opts": true,
+                "release": { "unstable": { "web-multithreading": true } },
+            },
+            "native": { "web": {} },
+            "preset": { "demo": { "feature": ["demo"] } },
+            "target-config": { "cfg(windows)": { "rustflag": [], "target": "x86_64-pc-windows-gnu" } },
+            "app": { "display-name": "Game", "iconn": "icon.png" },
+        });
+
+        let unknown_key = |key: &str, suggestion: Option<&str>| UnknownKey {
+            key: key.to_owned(),
+            suggestion: suggestion.map(ToOwned::to_owned),
+        };
+
+        assert_eq!(
+            unknown_keys(&config),
+            [
+                unknown_key("app.iconn", Some("icon")),
+                unknown_key("default_feature", Some("default-features")),
+                unknown_key("native.web", None),
+                unknown_key("preset.demo.feature", Some("features")),
+                unknown_key("target-config.cfg(windows).rustflag", Some("rustflags")),
+                unknown_key("target-config.cfg(windows).target", None),
+                unknown_key(
+                    "web.release.unstable.web-multithreading",
+                    Some("web-multi-threading")
+                ),
+                unknown_key("web.wasm-opts", Some("wasm-opt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_accept_all_config_keys() {
+        let config = json!({
+            "default_features": true,
+            "https": { "cert": "cert.pem", "key": "key.pem" },
+            "env": { "RUST_LOG": "debug" },
+            "dev": { "unstable": { "web-multi-threading": true } },
+            "web": { "release": { "wasm-opt": ["-Oz"] } },
+            "preset": { "profiling": { "features": ["bevy/trace_tracy"] } },
+            "target": "x86_64-pc-windows-gnu",
+            "target-config": { "x86_64-pc-windows-gnu": { "features": ["windows"] } },
+        });
+
+        assert!(unknown_keys(&config).is_empty());
+        assert!(unknown_keys(&json!({ "target": "wasm32-unknown-unknown" })).is_empty());
+    }
+
+    #[test]
+    fn should_parse_env_values_by_type() -> anyhow::Result<()> {
+        assert_eq!(env_value("wasm-opt", "false")?, json!(false));
+        assert_eq!(
+            env_value("wasm-opt", "-Oz --strip-debug")?,
+            json!(["-Oz", "--strip-debug"])
+        );
+        assert_eq!(
+            env_value("features", "dev tracing")?,
+            json!(["dev", "tracing"])
+        );
+        assert_eq!(
+            env_value("headers", r#"["Cache-Control: no-cache"]"#)?,
+            json!(["Cache-Control: no-cache"])
+        );
+        assert_eq!(
+            env_value("rustflags", "-C debuginfo=2")?,
+            json!("-C debuginfo=2")
+        );
+        assert_eq!(env_value("port", "3000")?, json!(3000));
+        assert_eq!(env_value("linker", "mold")?, json!("mold"));
+        assert_eq!(
+            env_value("env", r#"{ RUST_LOG = "debug" }"#)?,
+            json!({ "RUST_LOG": "debug" })
+        );
+        assert!(env_value("dynamic-linking", "yes").is_err());
+        assert!(env_value("port", "http").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_define_all_fields_of_the_config() {
+        let Value::Object(config) = serde_json::to_value(CliConfig::default()).unwrap() else {
+            panic!("the config must be serialized as object");
+        };
+        let properties = config_properties();
+
+        for key in config.keys() {
+            // Unstable options are nested in the `unstable` table
+            if key == "web-multi-threading" {
+                assert!(properties["unstable"]["properties"].get(key).is_some());
+            } else {
+                assert!(properties.contains_key(key), "`{key}` is missing");
+            }
+        }
+    }
+}
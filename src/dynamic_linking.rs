@@ -0,0 +1,113 @@
+//! Linking Bevy dynamically to speed up incremental compilation of native dev builds.
+//!
+//! Bevy's `dynamic_linking` feature compiles the engine into a dynamic library, which doesn't need
+//! to be re-linked when the app changes. It must never be enabled for release or web builds.
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Context as _;
+use cargo_metadata::{DependencyKind, Metadata, Package};
+use tracing::{debug, warn};
+
+use crate::external_cli::{CommandExt, cargo::install::AutoInstall};
+
+/// The name of Bevy's feature to link the engine dynamically.
+const DYNAMIC_LINKING_FEATURE: &str = "dynamic_linking";
+
+/// The environment variable that determines where dynamic libraries are searched.
+const LIBRARY_PATH_VAR: &str = if cfg!(windows) {
+    "PATH"
+} else if cfg!(target_os = "macos") {
+    "DYLD_FALLBACK_LIBRARY_PATH"
+} else {
+    "LD_LIBRARY_PATH"
+};
+
+/// Determine the feature that enables dynamic linking for the given package.
+///
+/// This is `bevy/dynamic_linking`, unless the Bevy dependency has been renamed.
+/// Returns `None` if the package doesn't depend on Bevy or if the resolved Bevy version doesn't
+/// support dynamic linking.
+pub(crate) fn bevy_dynamic_linking_feature(
+    metadata: &Metadata,
+    package: &Package,
+) -> Option<String> {
+    let Some(dependency) = package
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.name == "bevy" && dependency.kind == DependencyKind::Normal)
+    else {
+        warn!(
+            "dynamic linking is enabled, but `{}` doesn't depend on Bevy",
+            package.name
+        );
+        return None;
+    };
+
+    // The resolved dependency graph contains the Bevy version that is actually used,
+    // optional dependencies which are not enabled are missing
+    let bevy = metadata
+        .resolve
+        .as_ref()?
+        .nodes
+        .iter()
+        .find(|node| node.id == package.id)?
+        .deps
+        .iter()
+        .map(|dependency| &metadata[&dependency.pkg])
+        .find(|dependency| dependency.name.as_str() == "bevy");
+
+    let Some(bevy) = bevy else {
+        debug!("bevy is not enabled, skipping dynamic linking");
+        return None;
+    };
+
+    if !bevy.features.contains_key(DYNAMIC_LINKING_FEATURE) {
+        warn!(
+            "dynamic linking is enabled, but Bevy {} doesn't support it",
+            bevy.version
+        );
+        return None;
+    }
+
+    let name = dependency.rename.as_deref().unwrap_or("bevy");
+    Some(format!("{name}/{DYNAMIC_LINKING_FEATURE}"))
+}
+
+/// Add the directories containing the Bevy library and the Rust standard library to the dynamic
+/// library search path of the command.
+///
+/// `cargo run` does this automatically, but it's required when the binary is launched directly.
+pub(crate) fn set_library_path(
+    command: &mut Command,
+    profile_directory: &Path,
+) -> anyhow::Result<()> {
+    let mut paths = vec![profile_directory.join("deps"), rustc_target_libdir()?];
+    paths.extend(
+        env::var_os(LIBRARY_PATH_VAR)
+            .iter()
+            .flat_map(env::split_paths),
+    );
+
+    let library_path: OsString =
+        env::join_paths(paths).context("failed to build the library search path")?;
+    command.env(LIBRARY_PATH_VAR, library_path);
+
+    Ok(())
+}
+
+/// The directory containing the Rust standard library, which the Bevy library links to.
+fn rustc_target_libdir() -> anyhow::Result<PathBuf> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+
+    let output = CommandExt::new(rustc)
+        .args(["--print", "target-libdir"])
+        .output(AutoInstall::Never)
+        .context("failed to determine the location of the Rust standard library")?;
+
+    Ok(PathBuf::from(String::from_utf8(output.stdout)?.trim()))
+}
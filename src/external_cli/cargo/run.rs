@@ -55,9 +55,11 @@ impl CargoPackageRunArgs {
 #[derive(Debug, Args, Clone)]
 #[command(next_help_heading = "Target Selection")]
 pub struct CargoTargetRunArgs {
-    /// Build only the specified binary.
+    /// Run the specified binary.
+    ///
+    /// Can be passed multiple times to run several binaries at once.
     #[clap(long = "bin", value_name = "NAME")]
-    pub bin: Option<String>,
+    pub bin: Vec<String>,
 
     /// Build only the specified example.
     #[clap(long = "example", value_name = "NAME")]
@@ -67,7 +69,7 @@ pub struct CargoTargetRunArgs {
 impl CargoTargetRunArgs {
     pub(crate) fn args_builder(&self) -> ArgBuilder {
         ArgBuilder::new()
-            .add_opt_value("--bin", &self.bin)
+            .add_values_separately("--bin", &self.bin)
             .add_opt_value("--example", &self.example)
     }
 }
@@ -180,6 +180,17 @@ impl CommandExt {
         self
     }
 
+    /// Inserts or updates multiple explicit environment variable mappings.
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.inner.envs(vars);
+        self
+    }
+
     /// Define at which level the execution of the program should be logged.
     pub fn log_level(&mut self, level: Level) -> &mut Self {
         self.log_level = level;
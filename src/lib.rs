@@ -1,10 +1,18 @@
 //! The library backend for the prototype Bevy CLI.
 
 pub mod alias;
-#[cfg(feature = "web")]
+#[cfg(feature = "archive")]
+pub(crate) mod archive;
 pub(crate) mod bin_target;
 pub mod commands;
 pub(crate) mod config;
+pub(crate) mod config_schema;
+pub(crate) mod dynamic_linking;
 pub(crate) mod external_cli;
+pub(crate) mod linker;
+pub(crate) mod native;
+pub(crate) mod profiles;
+pub(crate) mod target_cfg;
+pub(crate) mod watch;
 #[cfg(feature = "web")]
 pub(crate) mod web;
@@ -0,0 +1,194 @@
+//! Using faster linkers for native builds.
+//!
+//! Linking Bevy takes up most of the time of incremental builds, which alternative linkers like
+//! `mold` and `lld` can reduce significantly.
+use std::{
+    env::{self, consts::EXE_SUFFIX},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use serde::Serialize;
+use tracing::{debug, info};
+
+/// The linker to use for native builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Linker {
+    /// Use the fastest linker that is installed.
+    Auto,
+    /// Use `mold`, driven by `clang`.
+    Mold,
+    /// Use LLVM's `lld`, driven by `clang`.
+    Lld,
+    /// Use the default linker of the platform.
+    Default,
+}
+
+impl Linker {
+    /// The programs which must be available in `PATH` to use the linker.
+    fn required_programs(self) -> &'static [&'static str] {
+        match self {
+            Self::Mold => &["mold", "clang"],
+            Self::Lld => &["ld.lld", "clang"],
+            Self::Auto | Self::Default => &[],
+        }
+    }
+
+    /// The rustflags to use the linker.
+    fn rustflags(self) -> Vec<String> {
+        let fuse_ld = match self {
+            Self::Mold => "mold",
+            Self::Lld => "lld",
+            Self::Auto | Self::Default => return Vec::new(),
+        };
+
+        vec![
+            "-Clinker=clang".to_owned(),
+            format!("-Clink-arg=-fuse-ld={fuse_ld}"),
+        ]
+    }
+}
+
+impl Display for Linker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Auto => "auto",
+            Self::Mold => "mold",
+            Self::Lld => "lld",
+            Self::Default => "default",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Determine the rustflags to use the configured linker for the given target.
+///
+/// Linker settings that the user already has, e.g. in `.cargo/config.toml`, are never overwritten.
+pub(crate) fn linker_rustflags(
+    linker: Linker,
+    target: &str,
+    configured_linker: Option<&Path>,
+    rustflags: &[String],
+) -> anyhow::Result<Vec<String>> {
+    if linker == Linker::Default || target.starts_with("wasm") {
+        debug!("using the default linker for {target}");
+        return Ok(Vec::new());
+    }
+
+    if let Some(configured_linker) = configured_linker {
+        info!(
+            "keeping the linker `{}` from the Cargo config",
+            configured_linker.display()
+        );
+        return Ok(Vec::new());
+    }
+
+    if rustflags.iter().any(|flag| configures_linker(flag)) {
+        info!("keeping the linker configured in the rustflags");
+        return Ok(Vec::new());
+    }
+
+    // Choosing the linker via `clang` only works with the GNU toolchains on Linux
+    if !target.contains("linux") {
+        if linker == Linker::Auto {
+            info!("using the default linker, because no faster linker is supported for {target}");
+            return Ok(Vec::new());
+        }
+        bail!("the {linker} linker is only supported for Linux targets, not for {target}");
+    }
+
+    if linker != Linker::Auto {
+        if let Some(missing) = missing_program(linker) {
+            bail!("the {linker} linker is configured, but `{missing}` was not found in PATH");
+        }
+
+        info!("using {linker} as linker, as configured");
+        return Ok(linker.rustflags());
+    }
+
+    for candidate in [Linker::Mold, Linker::Lld] {
+        if missing_program(candidate).is_none() {
+            info!(
+                "using {candidate} as linker, because `{}` were found in PATH",
+                candidate.required_programs().join("` and `")
+            );
+            return Ok(candidate.rustflags());
+        }
+    }
+
+    info!("using the default linker, because neither mold nor lld (with clang) were found in PATH");
+    Ok(Vec::new())
+}
+
+/// Whether the rustflag changes the linker or how it's invoked.
+fn configures_linker(flag: &str) -> bool {
+    flag.contains("linker=") || flag.contains("linker-flavor=") || flag.contains("-fuse-ld")
+}
+
+/// The first program required by the linker that is not available in `PATH`.
+fn missing_program(linker: Linker) -> Option<&'static str> {
+    linker
+        .required_programs()
+        .iter()
+        .find(|program| find_in_path(program).is_none())
+        .copied()
+}
+
+/// Search for an executable in the directories of the `PATH` environment variable.
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+
+    env::split_paths(&path)
+        .map(|directory| directory.join(format!("{program}{EXE_SUFFIX}")))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_linker_from_cargo_config() -> anyhow::Result<()> {
+        assert!(
+            linker_rustflags(
+                Linker::Mold,
+                "x86_64-unknown-linux-gnu",
+                Some(Path::new("gcc")),
+                &[]
+            )?
+            .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_linker_from_rustflags() -> anyhow::Result<()> {
+        for rustflags in [
+            vec!["-C".to_owned(), "link-arg=-fuse-ld=lld".to_owned()],
+            vec!["-Clinker=gcc".to_owned()],
+        ] {
+            assert!(
+                linker_rustflags(Linker::Mold, "x86_64-unknown-linux-gnu", None, &rustflags)?
+                    .is_empty()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_change_linker_for_web() -> anyhow::Result<()> {
+        assert!(linker_rustflags(Linker::Mold, "wasm32-unknown-unknown", None, &[])?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_explicit_linker_on_unsupported_targets() {
+        assert!(linker_rustflags(Linker::Lld, "x86_64-pc-windows-msvc", None, &[]).is_err());
+        assert!(
+            linker_rustflags(Linker::Auto, "x86_64-pc-windows-msvc", None, &[])
+                .is_ok_and(|rustflags| rustflags.is_empty())
+        );
+    }
+}
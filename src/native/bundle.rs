@@ -0,0 +1,162 @@
+//! Bundling native apps into a self-contained folder, which can be handed to players and testers.
+use std::{
+    env::consts::EXE_SUFFIX,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail};
+use cargo_metadata::Metadata;
+use fs_extra::dir::CopyOptions;
+use tracing::debug;
+
+use crate::bin_target::BinTarget;
+
+/// A native app packed into a single folder.
+#[derive(Debug, Clone)]
+pub struct NativeBundle {
+    /// The path to the folder containing the bundle.
+    pub path: PathBuf,
+}
+
+/// Create a folder containing the executable, the assets and additional files of the app.
+///
+/// The bundle is created at `target/bevy_native/{target}/{profile}/{bin_name}`,
+/// the target is omitted for host builds.
+/// The additional files are placed at their path relative to the package root.
+pub fn create_native_bundle(
+    metadata: &Metadata,
+    profile: &str,
+    target: Option<&str>,
+    bin_target: &BinTarget,
+    bundle_files: &[PathBuf],
+) -> anyhow::Result<NativeBundle> {
+    let package_root = bin_target
+        .package
+        .manifest_path
+        .parent()
+        .context("failed to find package root")?;
+
+    let mut base_path = metadata
+        .target_directory
+        .join("bevy_native")
+        .into_std_path_buf();
+    if let Some(target) = target {
+        base_path.push(target);
+    }
+    base_path.push(profile);
+    base_path.push(&bin_target.bin_name);
+
+    // Remove the previous bundle
+    // The error can be ignored, because the folder doesn't need to exist yet
+    let _ = fs::remove_dir_all(&base_path);
+    fs::create_dir_all(&base_path).context("failed to create bundle directory")?;
+
+    // Executable
+    let executable_name = format!("{}{}", bin_target.bin_name, exe_suffix(target));
+    let executable_path = bin_target.artifact_directory.join(&executable_name);
+    debug!(
+        "copying executable from file://{}",
+        executable_path.display()
+    );
+    fs::copy(&executable_path, base_path.join(&executable_name))
+        .with_context(|| format!("failed to copy {}", executable_path.display()))?;
+
+    // Assets
+    if let Some(assets_path) = bin_target.assets_path() {
+        debug!("copying assets from file://{}", assets_path.display());
+        fs_extra::dir::copy(
+            assets_path,
+            &base_path,
+            &CopyOptions {
+                overwrite: true,
+                ..Default::default()
+            },
+        )
+        .context("failed to copy assets")?;
+    }
+
+    // Additional files
+    for path in bundle_files {
+        let destination = base_path.join(bundle_file_destination(package_root.as_std_path(), path));
+        debug!(
+            "copying file://{} to file://{}",
+            path.display(),
+            destination.display()
+        );
+
+        if path.is_dir() {
+            fs::create_dir_all(&destination)?;
+            fs_extra::dir::copy(
+                path,
+                &destination,
+                &CopyOptions {
+                    overwrite: true,
+                    content_only: true,
+                    ..Default::default()
+                },
+            )
+            .with_context(|| format!("failed to copy {}", path.display()))?;
+        } else if path.is_file() {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(path, &destination)
+                .with_context(|| format!("failed to copy {}", path.display()))?;
+        } else {
+            bail!("the bundle file {} doesn't exist", path.display());
+        }
+    }
+
+    Ok(NativeBundle { path: base_path })
+}
+
+/// The file extension of executables for the given target.
+fn exe_suffix(target: Option<&str>) -> &'static str {
+    match target {
+        Some(target) if target.contains("windows") => ".exe",
+        Some(_) => "",
+        None => EXE_SUFFIX,
+    }
+}
+
+/// The path of an additional file inside of the bundle.
+///
+/// Files inside of the package keep their relative path, other files are placed at the root.
+fn bundle_file_destination(package_root: &Path, path: &Path) -> PathBuf {
+    match path.strip_prefix(package_root) {
+        Ok(relative_path)
+            if relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_))) =>
+        {
+            relative_path.to_path_buf()
+        }
+        _ => path.file_name().map(PathBuf::from).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_relative_path_of_bundle_files() {
+        let package_root = Path::new("/game");
+
+        assert_eq!(
+            bundle_file_destination(package_root, &package_root.join("licenses/MIT.txt")),
+            Path::new("licenses/MIT.txt")
+        );
+        assert_eq!(
+            bundle_file_destination(package_root, &package_root.join("../README.md")),
+            Path::new("README.md")
+        );
+    }
+
+    #[test]
+    fn should_determine_exe_suffix_of_target() {
+        assert_eq!(exe_suffix(Some("x86_64-pc-windows-msvc")), ".exe");
+        assert_eq!(exe_suffix(Some("x86_64-unknown-linux-gnu")), "");
+    }
+}
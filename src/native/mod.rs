@@ -0,0 +1,5 @@
+//! Utilities for distributing native apps.
+
+pub(crate) mod bundle;
+#[cfg(feature = "archive")]
+pub(crate) mod package;
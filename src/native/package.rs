@@ -0,0 +1,532 @@
+//! Packaging native apps for Linux, as Debian packages or AppImage-style directories.
+//!
+//! The packages are created from a native bundle, so they contain the executable, the assets and
+//! the additional bundle files. Everything is written by hand, no external tools are needed.
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail};
+use clap::ValueEnum;
+use fs_extra::dir::CopyOptions;
+use tracing::{debug, warn};
+
+use super::bundle::NativeBundle;
+use crate::{archive::write_package_tarball, bin_target::BinTarget, config::AppConfig};
+
+/// The supported Linux package formats.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageFormat {
+    /// A Debian package (`.deb`), which can be installed with `apt` or `dpkg`.
+    Deb,
+    /// An AppImage-style `AppDir`, which can be packed with `appimagetool`.
+    #[value(name = "appdir")]
+    AppDir,
+}
+
+/// The metadata of the app, as shown to the users of the package.
+struct AppMetadata<'a> {
+    /// The name of the executable.
+    bin_name: &'a str,
+    /// The human-readable name of the app.
+    display_name: String,
+    /// A short description of the app.
+    description: Option<&'a str>,
+    /// The categories of the app in the application menu.
+    categories: Vec<String>,
+    /// The icon of the app.
+    icon: Option<&'a Path>,
+}
+
+/// Package the native bundle in the given format.
+///
+/// The package is placed next to the bundle. Returns the path to the created package.
+pub(crate) fn create_package(
+    bundle: &NativeBundle,
+    bin_target: &BinTarget,
+    target: &str,
+    app: &AppConfig,
+    format: PackageFormat,
+) -> anyhow::Result<PathBuf> {
+    if !target.contains("linux") {
+        bail!("Linux packages can only be created for Linux targets, not for {target}");
+    }
+    if !cfg!(unix) {
+        bail!("Linux packages can only be created on Unix systems");
+    }
+
+    let package = bin_target.package;
+    let metadata = AppMetadata {
+        bin_name: &bin_target.bin_name,
+        display_name: app
+            .display_name
+            .clone()
+            .unwrap_or_else(|| package.name.to_string()),
+        description: package.description.as_deref(),
+        categories: app
+            .categories
+            .clone()
+            .unwrap_or_else(|| vec!["Game".to_owned()]),
+        icon: app.icon.as_deref(),
+    };
+
+    if let Some(icon) = metadata.icon
+        && !icon.is_file()
+    {
+        bail!("the app icon {} doesn't exist", icon.display());
+    }
+
+    let output_directory = bundle
+        .path
+        .parent()
+        .context("failed to determine the package directory")?;
+
+    match format {
+        PackageFormat::Deb => {
+            // The `Maintainer` field is mandatory in Debian packages
+            let Some(maintainer) = package.authors.first() else {
+                bail!(
+                    "Debian packages require a maintainer, add one to `authors` in the `[package]` section of `{}`, e.g. `authors = [\"Jane Doe <jane@example.com>\"]`",
+                    package.manifest_path
+                );
+            };
+            let package_name = debian_package_name(&package.name);
+            let version = debian_version(&package.version);
+            let architecture = debian_architecture(target)?;
+            let path =
+                output_directory.join(format!("{package_name}_{version}_{architecture}.deb"));
+
+            // The package contents are assembled in a temporary directory
+            let staging_directory = output_directory.join(format!(".{}-deb", metadata.bin_name));
+            let _ = fs::remove_dir_all(&staging_directory);
+            let data_directory = staging_directory.join("data");
+            let control_directory = staging_directory.join("control");
+
+            // Executable, assets and additional files
+            let lib_directory = data_directory.join("usr/lib").join(metadata.bin_name);
+            copy_bundle(&bundle.path, &lib_directory)?;
+            write_executable_script(
+                &data_directory.join("usr/bin").join(metadata.bin_name),
+                &format!(
+                    "#!/bin/sh\nexec /usr/lib/{0}/{0} \"$@\"\n",
+                    metadata.bin_name
+                ),
+            )?;
+
+            // Desktop entry and icon
+            write_file(
+                &data_directory
+                    .join("usr/share/applications")
+                    .join(format!("{}.desktop", metadata.bin_name)),
+                &desktop_entry(&metadata),
+            )?;
+            if let Some(icon) = metadata.icon {
+                let destination = data_directory
+                    .join("usr/share/icons/hicolor")
+                    .join(icon_size(icon)?)
+                    .join("apps")
+                    .join(icon_file_name(metadata.bin_name, icon));
+                copy_file(icon, &destination)?;
+            }
+
+            // License information
+            if let Some(license) = &package.license {
+                write_file(
+                    &data_directory
+                        .join("usr/share/doc")
+                        .join(&package_name)
+                        .join("copyright"),
+                    &format!(
+                        "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\nUpstream-Name: {}\n\nFiles: *\nLicense: {license}\n",
+                        package.name
+                    ),
+                )?;
+            }
+
+            let control = DebianControl {
+                package: &package_name,
+                version: &version,
+                architecture,
+                maintainer,
+                installed_size: directory_size(&data_directory)?.div_ceil(1024),
+                homepage: package.homepage.as_deref(),
+                description: metadata.description.unwrap_or(&metadata.display_name),
+            };
+            write_file(&control_directory.join("control"), &control.to_string())?;
+
+            let mut file = io::BufWriter::new(
+                fs::File::create(&path)
+                    .with_context(|| format!("failed to create {}", path.display()))?,
+            );
+            write_deb(&mut file, &control_directory, &data_directory)?;
+            file.flush()?;
+
+            fs::remove_dir_all(&staging_directory)?;
+
+            Ok(path)
+        }
+        PackageFormat::AppDir => {
+            let path = output_directory.join(format!("{}.AppDir", metadata.bin_name));
+            let _ = fs::remove_dir_all(&path);
+
+            copy_bundle(&bundle.path, &path.join("usr/lib").join(metadata.bin_name))?;
+            write_executable_script(
+                &path.join("AppRun"),
+                &format!(
+                    "#!/bin/sh\nHERE=\"$(dirname \"$(readlink -f \"$0\")\")\"\nexec \"$HERE/usr/lib/{0}/{0}\" \"$@\"\n",
+                    metadata.bin_name
+                ),
+            )?;
+            write_file(
+                &path.join(format!("{}.desktop", metadata.bin_name)),
+                &desktop_entry(&metadata),
+            )?;
+
+            if let Some(icon) = metadata.icon {
+                copy_file(icon, &path.join(icon_file_name(metadata.bin_name, icon)))?;
+            } else {
+                warn!(
+                    "no icon is configured in `app.icon`, which `appimagetool` requires to create an AppImage"
+                );
+            }
+
+            Ok(path)
+        }
+    }
+}
+
+/// The fields of the `control` file of a Debian package.
+///
+/// See <https://www.debian.org/doc/debian-policy/ch-controlfields.html#binary-package-control-files-debian-control>.
+struct DebianControl<'a> {
+    package: &'a str,
+    version: &'a str,
+    architecture: &'a str,
+    maintainer: &'a str,
+    /// The size of the installed files in KiB.
+    installed_size: u64,
+    homepage: Option<&'a str>,
+    description: &'a str,
+}
+
+impl std::fmt::Display for DebianControl<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Package: {}", self.package)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Architecture: {}", self.architecture)?;
+        writeln!(f, "Maintainer: {}", self.maintainer)?;
+        writeln!(f, "Installed-Size: {}", self.installed_size)?;
+        writeln!(f, "Section: games")?;
+        writeln!(f, "Priority: optional")?;
+        if let Some(homepage) = self.homepage {
+            writeln!(f, "Homepage: {homepage}")?;
+        }
+
+        // The first line is the synopsis, the following lines form the extended description
+        let mut lines = self.description.trim().lines();
+        writeln!(f, "Description: {}", lines.next().unwrap_or_default())?;
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                writeln!(f, " .")?;
+            } else {
+                writeln!(f, " {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Create the `.desktop` file which adds the app to the application menu.
+///
+/// See <https://specifications.freedesktop.org/desktop-entry-spec/latest/>.
+fn desktop_entry(metadata: &AppMetadata) -> String {
+    let mut entry = format!(
+        "[Desktop Entry]\nType=Application\nName={}\n",
+        metadata.display_name
+    );
+    if let Some(description) = metadata.description {
+        entry.push_str(&format!(
+            "Comment={}\n",
+            description.lines().next().unwrap_or_default().trim()
+        ));
+    }
+    entry.push_str(&format!("Exec={}\n", metadata.bin_name));
+    if metadata.icon.is_some() {
+        entry.push_str(&format!("Icon={}\n", metadata.bin_name));
+    }
+    entry.push_str(&format!("Categories={};\n", metadata.categories.join(";")));
+    entry.push_str("Terminal=false\n");
+    entry
+}
+
+/// Convert the name of a Cargo package into a valid Debian package name.
+///
+/// Debian package names may only contain lowercase letters, digits and `+`, `-` and `.`.
+fn debian_package_name(name: &str) -> String {
+    name.to_lowercase().replace('_', "-")
+}
+
+/// Convert the version of a Cargo package into a Debian version.
+///
+/// Pre-releases are separated with `~`, so that they are sorted before the final release.
+fn debian_version(version: &semver::Version) -> String {
+    let mut debian_version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    // Hyphens are reserved for the Debian revision
+    if !version.pre.is_empty() {
+        debian_version.push('~');
+        debian_version.push_str(&version.pre.replace('-', "."));
+    }
+    if !version.build.is_empty() {
+        debian_version.push('+');
+        debian_version.push_str(&version.build.replace('-', "."));
+    }
+    debian_version
+}
+
+/// The Debian architecture name of the given target.
+fn debian_architecture(target: &str) -> anyhow::Result<&'static str> {
+    let arch = target.split('-').next().unwrap_or_default();
+
+    Ok(match arch {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "i586" | "i686" => "i386",
+        "armv7" if target.ends_with("gnueabihf") => "armhf",
+        "arm" | "armv5te" => "armel",
+        "riscv64gc" => "riscv64",
+        "powerpc64le" => "ppc64el",
+        "s390x" => "s390x",
+        _ => bail!("the architecture of {target} is not supported by Debian packages"),
+    })
+}
+
+/// The size of the icon, as used in the directories of the icon theme.
+fn icon_size(icon: &Path) -> anyhow::Result<String> {
+    match icon.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => Ok("scalable".to_owned()),
+        Some("png") => {
+            let content =
+                fs::read(icon).with_context(|| format!("failed to read {}", icon.display()))?;
+            let (width, height) = png_dimensions(&content)
+                .with_context(|| format!("{} is not a valid PNG image", icon.display()))?;
+            Ok(format!("{width}x{height}"))
+        }
+        _ => bail!("the app icon must be a PNG or SVG image"),
+    }
+}
+
+/// Read the width and height from the header of a PNG image.
+fn png_dimensions(content: &[u8]) -> Option<(u32, u32)> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    // The signature is followed by the `IHDR` chunk, which starts with the dimensions
+    if !content.starts_with(SIGNATURE) || content.get(12..16)? != b"IHDR" {
+        return None;
+    }
+
+    let width = u32::from_be_bytes(content.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(content.get(20..24)?.try_into().ok()?);
+    Some((width, height))
+}
+
+/// The file name of the icon, named after the executable to match the desktop entry.
+fn icon_file_name(bin_name: &str, icon: &Path) -> String {
+    match icon.extension() {
+        Some(extension) => format!("{bin_name}.{}", extension.to_string_lossy()),
+        None => bin_name.to_owned(),
+    }
+}
+
+/// Write the Debian package, an `ar` archive with the control and data tarballs.
+///
+/// See <https://manpages.debian.org/deb.5>.
+fn write_deb(
+    writer: &mut impl Write,
+    control_directory: &Path,
+    data_directory: &Path,
+) -> anyhow::Result<()> {
+    let control = write_package_tarball(control_directory, Vec::new())
+        .context("failed to pack the control files")?;
+    let data = write_package_tarball(data_directory, Vec::new())
+        .context("failed to pack the package files")?;
+
+    writer.write_all(b"!<arch>\n")?;
+    write_ar_member(writer, "debian-binary", b"2.0\n")?;
+    write_ar_member(writer, "control.tar.gz", &control)?;
+    write_ar_member(writer, "data.tar.gz", &data)?;
+
+    Ok(())
+}
+
+/// Write a file into an `ar` archive.
+///
+/// The timestamp and owner are fixed to keep the package deterministic.
+fn write_ar_member(writer: &mut impl Write, name: &str, content: &[u8]) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{name:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`",
+        0,
+        0,
+        0,
+        100644,
+        content.len()
+    )?;
+    writer.write_all(content)?;
+
+    // The members are aligned to an even number of bytes
+    if content.len() % 2 == 1 {
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// Copy the contents of the bundle into the given directory.
+fn copy_bundle(bundle: &Path, destination: &Path) -> anyhow::Result<()> {
+    debug!(
+        "copying bundle from file://{} to file://{}",
+        bundle.display(),
+        destination.display()
+    );
+    fs::create_dir_all(destination)?;
+    fs_extra::dir::copy(
+        bundle,
+        destination,
+        &CopyOptions {
+            overwrite: true,
+            content_only: true,
+            ..Default::default()
+        },
+    )
+    .context("failed to copy the bundle")?;
+
+    Ok(())
+}
+
+/// Copy a file, creating the parent directories if needed.
+fn copy_file(source: &Path, destination: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(source, destination)
+        .with_context(|| format!("failed to copy {}", source.display()))?;
+
+    Ok(())
+}
+
+/// Write a file, creating the parent directories if needed.
+fn write_file(path: &Path, content: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Write a shell script that can be executed.
+fn write_executable_script(path: &Path, content: &str) -> anyhow::Result<()> {
+    write_file(path, content)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(())
+}
+
+/// The total size of all files in the directory, in bytes.
+fn directory_size(directory: &Path) -> io::Result<u64> {
+    let mut size = 0;
+
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            size += directory_size(&entry.path())?;
+        } else {
+            size += entry.metadata()?.len();
+        }
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_versions_for_debian() {
+        assert_eq!(
+            debian_version(&semver::Version::new(1, 2, 3)),
+            "1.2.3".to_owned()
+        );
+        assert_eq!(
+            debian_version(&"0.1.0-rc-1+build-5".parse().unwrap()),
+            "0.1.0~rc.1+build.5".to_owned()
+        );
+    }
+
+    #[test]
+    fn should_map_targets_to_debian_architectures() -> anyhow::Result<()> {
+        assert_eq!(debian_architecture("x86_64-unknown-linux-gnu")?, "amd64");
+        assert_eq!(debian_architecture("aarch64-unknown-linux-gnu")?, "arm64");
+        assert_eq!(
+            debian_architecture("armv7-unknown-linux-gnueabihf")?,
+            "armhf"
+        );
+        assert!(debian_architecture("wasm32-unknown-unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_format_debian_control_file() {
+        let control = DebianControl {
+            package: "my-game",
+            version: "0.1.0",
+            architecture: "amd64",
+            maintainer: "Jane Doe <jane@example.com>",
+            installed_size: 42,
+            homepage: None,
+            description: "A small game.\n\nWith a longer description.",
+        };
+
+        assert_eq!(
+            control.to_string(),
+            "Package: my-game\nVersion: 0.1.0\nArchitecture: amd64\nMaintainer: Jane Doe <jane@example.com>\nInstalled-Size: 42\nSection: games\nPriority: optional\nDescription: A small game.\n .\n With a longer description.\n"
+        );
+    }
+
+    #[test]
+    fn should_create_desktop_entry() {
+        let metadata = AppMetadata {
+            bin_name: "my_game",
+            display_name: "My Game".to_owned(),
+            description: Some("A small game."),
+            categories: vec!["Game".to_owned(), "ActionGame".to_owned()],
+            icon: Some(Path::new("assets/icon.png")),
+        };
+
+        assert_eq!(
+            desktop_entry(&metadata),
+            "[Desktop Entry]\nType=Application\nName=My Game\nComment=A small game.\nExec=my_game\nIcon=my_game\nCategories=Game;ActionGame;\nTerminal=false\n"
+        );
+    }
+
+    #[test]
+    fn should_read_png_dimensions() {
+        let mut header = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        header.extend(256u32.to_be_bytes());
+        header.extend(128u32.to_be_bytes());
+
+        assert_eq!(png_dimensions(&header), Some((256, 128)));
+        assert_eq!(png_dimensions(b"GIF89a"), None);
+    }
+}
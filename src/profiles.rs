@@ -0,0 +1,243 @@
+//! Default compilation profiles, which are configured unless the user defines them.
+use std::{collections::HashMap, env, fs};
+
+use anyhow::Context as _;
+use cargo_metadata::Metadata;
+use toml::Table;
+use tracing::info;
+
+/// The profile for optimized native builds that are distributed to players.
+pub(crate) const NATIVE_DIST_PROFILE: &str = "native-dist";
+
+/// Create `--config` args to configure the default profiles to use when compiling for the web.
+#[cfg(feature = "web")]
+pub(crate) fn configure_default_web_profiles(metadata: &Metadata) -> anyhow::Result<Vec<String>> {
+    let defined_profiles = DefinedProfiles::load(metadata)?;
+
+    let mut args = Vec::new();
+
+    if !defined_profiles.contains("web") {
+        configure_web_profile(&mut args);
+    }
+
+    if !defined_profiles.contains("web-release") {
+        configure_web_release_profile(&mut args);
+    }
+
+    Ok(args)
+}
+
+/// Create `--config` args to configure the default profiles to use when compiling natively.
+pub(crate) fn configure_default_native_profiles(
+    metadata: &Metadata,
+) -> anyhow::Result<Vec<String>> {
+    let defined_profiles = DefinedProfiles::load(metadata)?;
+
+    let mut args = Vec::new();
+
+    if !defined_profiles.contains("dev") {
+        info!(
+            "using the default `dev` profile (`opt-level = 1`, `opt-level = 3` for dependencies), \
+             define `[profile.dev]` to customize it"
+        );
+        configure_native_dev_profile(&mut args);
+    }
+
+    if !defined_profiles.contains(NATIVE_DIST_PROFILE) {
+        configure_native_dist_profile(&mut args);
+    }
+
+    Ok(args)
+}
+
+/// The configuration sources in which the user can define profiles.
+struct DefinedProfiles {
+    /// The workspace manifest.
+    manifest: Table,
+    /// The Cargo config files, e.g. `.cargo/config.toml`.
+    cargo_configs: Vec<Table>,
+}
+
+impl DefinedProfiles {
+    /// Read the workspace manifest and the Cargo config files.
+    ///
+    /// Like Cargo, the config files are searched in the current directory and its parents, and
+    /// in the Cargo home directory.
+    fn load(metadata: &Metadata) -> anyhow::Result<Self> {
+        let manifest = fs::read_to_string(metadata.workspace_root.join("Cargo.toml"))
+            .context("failed to read workspace manifest")?
+            .parse::<Table>()
+            .context("failed to parse workspace manifest")?;
+
+        let current_dir = env::current_dir()?;
+        let cargo_configs = cargo_config2::Walk::new(&current_dir)
+            .map(|path| {
+                fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?
+                    .parse::<Table>()
+                    .with_context(|| format!("failed to parse {}", path.display()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            manifest,
+            cargo_configs,
+        })
+    }
+
+    /// Whether the user has customized the given profile.
+    ///
+    /// This includes `CARGO_PROFILE_<name>_*` environment variables.
+    fn contains(&self, profile: &str) -> bool {
+        let env_prefix = format!(
+            "CARGO_PROFILE_{}_",
+            profile.to_uppercase().replace('-', "_")
+        );
+
+        is_profile_defined_in_manifest(&self.manifest, profile)
+            || self
+                .cargo_configs
+                .iter()
+                .any(|config| is_profile_defined_in_manifest(config, profile))
+            || env::vars_os().any(|(key, _)| key.to_string_lossy().starts_with(&env_prefix))
+    }
+}
+
+fn is_profile_defined_in_manifest(manifest: &Table, profile: &str) -> bool {
+    manifest
+        .get("profile")
+        .is_some_and(|profiles| profiles.get(profile).is_some())
+}
+
+/// Configure the default profile for web debug builds.
+///
+/// It is optimized for fast iteration speeds.
+#[cfg(feature = "web")]
+fn configure_web_profile(args: &mut Vec<String>) {
+    configure_profile("web", Some("dev"), HashMap::new(), args);
+}
+
+/// Configure the default profile for web release builds.
+///
+/// It is optimized both for run time performance and loading times.
+#[cfg(feature = "web")]
+fn configure_web_release_profile(args: &mut Vec<String>) {
+    let config = HashMap::from_iter([
+        // Optimize for size, greatly reducing loading times
+        ("opt-level", r#""s""#),
+        // Remove debug information, reducing file size further
+        ("strip", r#""debuginfo""#),
+    ]);
+    configure_profile("web-release", Some("release"), config, args);
+}
+
+/// Configure the default profile for native debug builds.
+///
+/// Bevy is too slow without optimizations, so the app is slightly optimized and its dependencies
+/// are fully optimized. The dependencies rarely change, so this barely affects compile times.
+fn configure_native_dev_profile(args: &mut Vec<String>) {
+    let config = HashMap::from_iter([("opt-level", "1"), (r#"package."*".opt-level"#, "3")]);
+    configure_profile("dev", None, config, args);
+}
+
+/// Configure the default profile for distributing native apps.
+///
+/// It trades longer compile times for the best run time performance.
+fn configure_native_dist_profile(args: &mut Vec<String>) {
+    let config = HashMap::from_iter([
+        // Optimize across crate boundaries
+        ("lto", "true"),
+        // Allow more optimizations by compiling each crate as a whole
+        ("codegen-units", "1"),
+    ]);
+    configure_profile(NATIVE_DIST_PROFILE, Some("release"), config, args);
+}
+
+/// Create `--config` args for `cargo` to configure a compilation profile.
+///
+/// The values of the config must be valid TOML, e.g. strings need to be quoted.
+/// Equivalent to a `Cargo.toml` like this:
+///
+/// ```toml
+/// [profile.{profile}]
+/// inherits = "{inherits}"
+/// # config
+/// key = value
+/// ```
+fn configure_profile(
+    profile: &str,
+    inherits: Option<&str>,
+    config: HashMap<&str, &str>,
+    args: &mut Vec<String>,
+) {
+    if let Some(inherits) = inherits {
+        args.push(format!(r#"profile.{profile}.inherits="{inherits}""#));
+    }
+
+    for (key, value) in config {
+        args.push(format!("profile.{profile}.{key}={value}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_defined_profile() {
+        let manifest = r#"
+        [profile.web]
+        inherits = "dev"
+        "#
+        .parse()
+        .unwrap();
+
+        assert!(is_profile_defined_in_manifest(&manifest, "web"));
+    }
+
+    #[test]
+    fn should_detect_missing_profile() {
+        let manifest = r#"
+        [profile.foo]
+        inherits = "dev"
+        "#
+        .parse()
+        .unwrap();
+
+        assert!(!is_profile_defined_in_manifest(&manifest, "web"));
+    }
+
+    #[test]
+    fn should_prefer_profiles_from_cargo_config() {
+        let profiles = DefinedProfiles {
+            manifest: Table::new(),
+            cargo_configs: vec![
+                r#"
+                [profile.dev]
+                opt-level = 0
+                "#
+                .parse()
+                .unwrap(),
+            ],
+        };
+
+        assert!(profiles.contains("dev"));
+        assert!(!profiles.contains(NATIVE_DIST_PROFILE));
+    }
+
+    #[test]
+    fn should_configure_profile_values_as_toml() {
+        let mut args = Vec::new();
+        configure_native_dist_profile(&mut args);
+        args.sort();
+
+        assert_eq!(
+            args,
+            vec![
+                "profile.native-dist.codegen-units=1",
+                r#"profile.native-dist.inherits="release""#,
+                "profile.native-dist.lto=true",
+            ]
+        );
+    }
+}
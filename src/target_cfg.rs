@@ -0,0 +1,248 @@
+//! Matching the `target` sections of the config against the targeted platform.
+//!
+//! Like Cargo's `[target]` tables, a section either names a target triple, e.g.
+//! `x86_64-pc-windows-gnu`, or a `cfg(...)` expression, e.g. `cfg(target_os = "windows")`.
+use std::{env, ffi::OsString, iter::Peekable, str::Chars};
+
+use anyhow::{Context as _, bail};
+
+use crate::external_cli::{CommandExt, cargo::install::AutoInstall};
+
+/// The targeted platform together with its `cfg` values, as printed by `rustc --print cfg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TargetCfg {
+    /// The target triple, e.g. `x86_64-pc-windows-gnu`.
+    triple: String,
+    /// The `cfg` values of the target, e.g. `unix` or `target_os="linux"`.
+    cfg: Vec<(String, Option<String>)>,
+}
+
+/// A parsed `cfg(...)` expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CfgExpr {
+    /// A name with an optional value, e.g. `unix` or `target_os = "linux"`.
+    Value(String, Option<String>),
+    All(Vec<CfgExpr>),
+    Any(Vec<CfgExpr>),
+    Not(Box<CfgExpr>),
+}
+
+impl TargetCfg {
+    /// Query the `cfg` values of the given target from `rustc`.
+    ///
+    /// Defaults to the host platform if no target is given.
+    pub(crate) fn query(triple: Option<&str>) -> anyhow::Result<Self> {
+        let triple = match triple {
+            Some(triple) => triple.to_owned(),
+            None => host_triple()?,
+        };
+
+        let output = CommandExt::new(rustc())
+            .args(["--print", "cfg", "--target", &triple])
+            .output(AutoInstall::Never)
+            .with_context(|| format!("failed to determine the cfg values of `{triple}`"))?;
+
+        Ok(Self::from_rustc_output(
+            triple,
+            &String::from_utf8(output.stdout)?,
+        ))
+    }
+
+    /// Parse the output of `rustc --print cfg`, with one `name` or `name="value"` per line.
+    pub(crate) fn from_rustc_output(triple: String, output: &str) -> Self {
+        let cfg = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.split_once('=') {
+                Some((name, value)) => (name.to_owned(), Some(value.trim_matches('"').to_owned())),
+                None => (line.to_owned(), None),
+            })
+            .collect();
+
+        Self { triple, cfg }
+    }
+
+    /// The target triple, e.g. `x86_64-pc-windows-gnu`.
+    pub(crate) fn triple(&self) -> &str {
+        &self.triple
+    }
+
+    /// Whether the key of a `target` section applies to the target.
+    ///
+    /// The key is either a target triple or a `cfg(...)` expression.
+    pub(crate) fn matches(&self, key: &str) -> anyhow::Result<bool> {
+        let Some(expr) = key
+            .strip_prefix("cfg(")
+            .and_then(|expr| expr.strip_suffix(')'))
+        else {
+            return Ok(key == self.triple);
+        };
+
+        let expr = CfgExpr::parse(expr).with_context(|| format!("invalid target `{key}`"))?;
+        Ok(expr.eval(&self.cfg))
+    }
+}
+
+impl CfgExpr {
+    /// Parse an expression like `all(unix, target_arch = "x86_64")`.
+    fn parse(expr: &str) -> anyhow::Result<Self> {
+        let mut chars = expr.chars().peekable();
+        let parsed = Self::parse_expr(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+        if let Some(char) = chars.next() {
+            bail!("unexpected `{char}`");
+        }
+        Ok(parsed)
+    }
+
+    fn parse_expr(chars: &mut Peekable<Chars<'_>>) -> anyhow::Result<Self> {
+        let name = parse_identifier(chars)?;
+        skip_whitespace(chars);
+
+        match (name.as_str(), chars.peek()) {
+            ("all" | "any" | "not", Some('(')) => {
+                chars.next();
+                let mut exprs = Vec::new();
+                loop {
+                    skip_whitespace(chars);
+                    if chars.next_if_eq(&')').is_some() {
+                        break;
+                    }
+                    exprs.push(Self::parse_expr(chars)?);
+                    skip_whitespace(chars);
+                    match chars.next() {
+                        Some(',') => {}
+                        Some(')') => break,
+                        _ => bail!("expected `,` or `)` in `{name}(...)`"),
+                    }
+                }
+
+                match name.as_str() {
+                    "all" => Ok(Self::All(exprs)),
+                    "any" => Ok(Self::Any(exprs)),
+                    _ => match <[_; 1]>::try_from(exprs) {
+                        Ok([expr]) => Ok(Self::Not(Box::new(expr))),
+                        Err(_) => bail!("`not(...)` must contain exactly one expression"),
+                    },
+                }
+            }
+            (_, Some('=')) => {
+                chars.next();
+                skip_whitespace(chars);
+                if chars.next() != Some('"') {
+                    bail!("the value of `{name}` must be a string");
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(char) => value.push(char),
+                        None => bail!("unterminated string in the value of `{name}`"),
+                    }
+                }
+                Ok(Self::Value(name, Some(value)))
+            }
+            _ => Ok(Self::Value(name, None)),
+        }
+    }
+
+    /// Whether the expression holds for the given `cfg` values.
+    fn eval(&self, cfg: &[(String, Option<String>)]) -> bool {
+        match self {
+            Self::Value(name, value) => cfg
+                .iter()
+                .any(|(cfg_name, cfg_value)| cfg_name == name && cfg_value == value),
+            Self::All(exprs) => exprs.iter().all(|expr| expr.eval(cfg)),
+            Self::Any(exprs) => exprs.iter().any(|expr| expr.eval(cfg)),
+            Self::Not(expr) => !expr.eval(cfg),
+        }
+    }
+}
+
+fn parse_identifier(chars: &mut Peekable<Chars<'_>>) -> anyhow::Result<String> {
+    skip_whitespace(chars);
+    let mut identifier = String::new();
+    while let Some(char) = chars.next_if(|char| char.is_alphanumeric() || *char == '_') {
+        identifier.push(char);
+    }
+
+    if identifier.is_empty() {
+        match chars.peek() {
+            Some(char) => bail!("expected a cfg name, found `{char}`"),
+            None => bail!("expected a cfg name"),
+        }
+    }
+    Ok(identifier)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+}
+
+/// The `rustc` executable, which can be overridden with the `RUSTC` environment variable.
+fn rustc() -> OsString {
+    env::var_os("RUSTC").unwrap_or_else(|| "rustc".into())
+}
+
+/// The target triple of the host platform.
+fn host_triple() -> anyhow::Result<String> {
+    let output = CommandExt::new(rustc())
+        .arg("-vV")
+        .output(AutoInstall::Never)
+        .context("failed to determine the host platform")?;
+
+    String::from_utf8(output.stdout)?
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(|triple| triple.trim().to_owned())
+        .context("`rustc -vV` doesn't list the host platform")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windows_gnu() -> TargetCfg {
+        TargetCfg::from_rustc_output(
+            "x86_64-pc-windows-gnu".to_owned(),
+            "debug_assertions\ntarget_arch=\"x86_64\"\ntarget_env=\"gnu\"\n\
+             target_family=\"windows\"\ntarget_os=\"windows\"\nwindows\n",
+        )
+    }
+
+    #[test]
+    fn should_match_target_triple() -> anyhow::Result<()> {
+        let target = windows_gnu();
+
+        assert!(target.matches("x86_64-pc-windows-gnu")?);
+        assert!(!target.matches("x86_64-pc-windows-msvc")?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_evaluate_cfg_expressions() -> anyhow::Result<()> {
+        let target = windows_gnu();
+
+        assert!(target.matches("cfg(windows)")?);
+        assert!(target.matches(r#"cfg(target_os = "windows")"#)?);
+        assert!(target.matches(r#"cfg(all(windows, target_env="gnu"))"#)?);
+        assert!(target.matches(r#"cfg(any(unix, target_arch = "x86_64"))"#)?);
+        assert!(target.matches("cfg(not(unix))")?);
+        assert!(!target.matches(r#"cfg(all(windows, target_env = "msvc"))"#)?);
+        assert!(!target.matches("cfg(any())")?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_invalid_cfg_expressions() {
+        let target = windows_gnu();
+
+        assert!(target.matches("cfg(not(unix, windows))").is_err());
+        assert!(target.matches("cfg(target_os = windows)").is_err());
+        assert!(target.matches("cfg(windows unix)").is_err());
+        assert!(target.matches("cfg()").is_err());
+        assert!(target.matches(r#"cfg(target_os = "windows)"#).is_err());
+    }
+}
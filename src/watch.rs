@@ -0,0 +1,340 @@
+//! Watching files for changes, used to rebuild the app automatically.
+//!
+//! The watcher periodically polls the modification times of the watched files instead of relying
+//! on platform-specific file system events. This keeps the dependency footprint low and works the
+//! same on every platform, at the cost of a small delay until changes are detected.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use cargo_metadata::{DependencyKind, Metadata, Package, TargetKind};
+
+/// The interval in which the watched paths are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// How long the files must stay unchanged before the changes are reported.
+///
+/// Editors and build tools often write multiple files in quick succession,
+/// which should only trigger a single rebuild.
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
+
+/// Watches a set of files and directories for changes.
+#[derive(Debug)]
+pub(crate) struct FileWatcher {
+    /// The files and directories to watch.
+    ///
+    /// Directories are watched recursively.
+    paths: Vec<PathBuf>,
+    /// The last known modification time of every watched file.
+    snapshot: HashMap<PathBuf, SystemTime>,
+}
+
+impl FileWatcher {
+    /// Start watching the given files and directories.
+    ///
+    /// Paths that don't exist (yet) are ignored until they are created.
+    pub(crate) fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        let snapshot = take_snapshot(&paths);
+
+        Self { paths, snapshot }
+    }
+
+    /// The files and directories that are being watched.
+    pub(crate) fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Check the watched paths once and return all files that have been added, modified or
+    /// removed since the last check.
+    pub(crate) fn poll_changes(&mut self) -> Vec<PathBuf> {
+        let snapshot = take_snapshot(&self.paths);
+        let changes = diff_snapshots(&self.snapshot, &snapshot);
+        self.snapshot = snapshot;
+        changes
+    }
+
+    /// Block until at least one of the watched files changed.
+    ///
+    /// Returns the changed files once no further changes have been made for a short duration.
+    pub(crate) fn wait_for_changes(&mut self) -> Vec<PathBuf> {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let mut changes = self.poll_changes();
+
+            if changes.is_empty() {
+                continue;
+            }
+
+            // Wait until the files settle down
+            loop {
+                thread::sleep(DEBOUNCE_DURATION);
+
+                let more_changes = self.poll_changes();
+
+                if more_changes.is_empty() {
+                    break;
+                }
+
+                changes.extend(more_changes);
+            }
+
+            changes.sort();
+            changes.dedup();
+            return changes;
+        }
+    }
+}
+
+/// The files and directories containing the sources of the given package.
+///
+/// This includes the manifest and the directories of all compilation targets, except for tests
+/// and benchmarks, which don't affect the app.
+pub(crate) fn package_source_paths(package: &Package) -> Vec<PathBuf> {
+    let Some(package_root) = package.manifest_path.parent() else {
+        return vec![package.manifest_path.clone().into()];
+    };
+
+    let mut paths = vec![package.manifest_path.clone().into_std_path_buf()];
+
+    for target in &package.targets {
+        if target
+            .kind
+            .iter()
+            .all(|kind| matches!(kind, TargetKind::Test | TargetKind::Bench))
+        {
+            continue;
+        }
+
+        let path = match target.src_path.parent() {
+            // Watching the package root would include the `target` folder,
+            // so only the file itself is watched in that case (e.g. for `build.rs`)
+            Some(parent) if parent != package_root => parent,
+            _ => &target.src_path,
+        };
+
+        paths.push(path.to_path_buf().into_std_path_buf());
+    }
+
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// The source paths of the given package and all its local path dependencies.
+///
+/// Path dependencies are resolved recursively, development dependencies are skipped because they
+/// don't affect the app.
+pub(crate) fn watched_source_paths(metadata: &Metadata, package: &Package) -> Vec<PathBuf> {
+    let mut packages = vec![package];
+    let mut paths = Vec::new();
+    let mut index = 0;
+
+    while let Some(package) = packages.get(index) {
+        paths.extend(package_source_paths(package));
+
+        for dependency in &package.dependencies {
+            if dependency.kind == DependencyKind::Development {
+                continue;
+            }
+            let Some(dependency_path) = &dependency.path else {
+                continue;
+            };
+
+            let dependency_package = metadata
+                .packages
+                .iter()
+                .find(|package| package.manifest_path.parent() == Some(dependency_path.as_path()));
+
+            if let Some(dependency_package) = dependency_package
+                && !packages.contains(&dependency_package)
+            {
+                packages.push(dependency_package);
+            }
+        }
+
+        index += 1;
+    }
+
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Record the modification times of all files in the given paths.
+fn take_snapshot(paths: &[PathBuf]) -> HashMap<PathBuf, SystemTime> {
+    let mut snapshot = HashMap::new();
+
+    for path in paths {
+        visit_files(path, &mut snapshot);
+    }
+
+    snapshot
+}
+
+/// Recursively record the modification times of the files at the given path.
+fn visit_files(path: &Path, snapshot: &mut HashMap<PathBuf, SystemTime>) {
+    // Files can be removed while we are visiting them, so errors are ignored
+    let Ok(metadata) = fs::metadata(path) else {
+        return;
+    };
+
+    if metadata.is_dir() {
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Symlinks to directories could form a loop, e.g. when pointing to a parent directory
+            let is_linked_dir = entry
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_symlink())
+                && path.is_dir();
+            if !is_linked_dir {
+                visit_files(&path, snapshot);
+            }
+        }
+    } else if let Ok(modified) = metadata.modified() {
+        snapshot.insert(path.to_path_buf(), modified);
+    }
+}
+
+/// Determine the files that differ between two snapshots.
+fn diff_snapshots(
+    old: &HashMap<PathBuf, SystemTime>,
+    new: &HashMap<PathBuf, SystemTime>,
+) -> Vec<PathBuf> {
+    let modified_or_added = new
+        .iter()
+        .filter(|(path, modified)| old.get(*path) != Some(modified))
+        .map(|(path, _)| path.clone());
+    let removed = old.keys().filter(|path| !new.contains_key(*path)).cloned();
+
+    let mut changes: Vec<PathBuf> = modified_or_added.chain(removed).collect();
+    changes.sort();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    fn snapshot(files: &[(&str, u64)]) -> HashMap<PathBuf, SystemTime> {
+        files
+            .iter()
+            .map(|(path, secs)| (PathBuf::from(path), UNIX_EPOCH + Duration::from_secs(*secs)))
+            .collect()
+    }
+
+    #[test]
+    fn should_detect_added_modified_and_removed_files() {
+        let old = snapshot(&[("a.rs", 1), ("b.rs", 1), ("c.rs", 1)]);
+        let new = snapshot(&[("a.rs", 1), ("b.rs", 2), ("d.rs", 1)]);
+
+        assert_eq!(
+            diff_snapshots(&old, &new),
+            vec![
+                PathBuf::from("b.rs"),
+                PathBuf::from("c.rs"),
+                PathBuf::from("d.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_no_changes_for_identical_snapshots() {
+        let old = snapshot(&[("a.rs", 1)]);
+
+        assert!(diff_snapshots(&old, &old.clone()).is_empty());
+    }
+
+    #[test]
+    fn should_detect_new_files_in_watched_directory() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut watcher = FileWatcher::new([dir.path().to_path_buf()]);
+
+        let file = dir.path().join("main.rs");
+        fs::write(&file, "fn main() {}")?;
+
+        assert_eq!(watcher.poll_changes(), vec![file]);
+        assert!(watcher.poll_changes().is_empty());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_not_follow_symlinked_directories() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+        std::os::unix::fs::symlink("..", dir.path().join("parent"))?;
+
+        let snapshot = take_snapshot(&[dir.path().to_path_buf()]);
+
+        assert_eq!(
+            snapshot.into_keys().collect::<Vec<_>>(),
+            vec![dir.path().join("main.rs")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_watch_local_path_dependencies() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let manifest = |name: &str, dependencies: &str| {
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2024\"\n{dependencies}"
+            )
+        };
+        for (name, dependencies) in [
+            (
+                "app",
+                "[dependencies]\nutil = { path = \"../util\" }\n[dev-dependencies]\ntesting = { path = \"../testing\" }",
+            ),
+            ("util", "[dependencies]\nmath = { path = \"../math\" }"),
+            ("math", ""),
+            ("testing", ""),
+        ] {
+            let src = dir.path().join(name).join("src");
+            fs::create_dir_all(&src)?;
+            fs::write(src.join("lib.rs"), "")?;
+            fs::create_dir_all(dir.path().join(name).join("tests"))?;
+            fs::write(dir.path().join(name).join("tests").join("it.rs"), "")?;
+            fs::write(
+                dir.path().join(name).join("Cargo.toml"),
+                manifest(name, dependencies),
+            )?;
+        }
+
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .current_dir(dir.path().join("app"))
+            .other_options(vec!["--offline".to_owned()])
+            .exec()?;
+        let app = metadata
+            .packages
+            .iter()
+            .find(|package| package.name.as_str() == "app")
+            .unwrap();
+
+        let paths = watched_source_paths(&metadata, app);
+        let watches = |name: &str| {
+            paths
+                .iter()
+                .any(|path| path.ends_with(Path::new(name).join("src")))
+        };
+
+        assert!(watches("app") && watches("util") && watches("math"));
+        assert!(!watches("testing"));
+        // Changes to tests don't affect the running app
+        assert!(!paths.iter().any(|path| path.ends_with("tests")));
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! Passing the arguments and environment variables of `bevy run` to the app in the browser.
+//!
+//! Wasm apps don't have a command line, so the values are provided as the `window.bevyCli` global
+//! and the arguments additionally as query parameters of the page URL.
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// The arguments and environment variables for the app, as exposed to the browser.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct AppArgs {
+    /// The arguments passed after `--`.
+    pub args: Vec<String>,
+    /// The environment variables from the config.
+    pub env: BTreeMap<String, String>,
+}
+
+impl AppArgs {
+    /// Whether there is nothing to pass to the app.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.args.is_empty() && self.env.is_empty()
+    }
+
+    /// Inject a script defining the `window.bevyCli` global into the `index.html`.
+    ///
+    /// The script is placed in the `<head>`, so that it runs before the app is loaded.
+    pub(crate) fn inject(&self, index: &str) -> String {
+        if self.is_empty() {
+            return index.to_owned();
+        }
+
+        let json = serde_json::to_string(self).unwrap_or_default();
+        // A `</script>` inside of the values must not end the script early
+        let script = format!(
+            "<script>window.bevyCli = {};</script>",
+            json.replace("</", r"<\/")
+        );
+
+        if index.contains("</head>") {
+            index.replacen("</head>", &format!("{script}</head>"), 1)
+        } else {
+            format!("{script}{index}")
+        }
+    }
+
+    /// The arguments as query string for the page URL, e.g. `?level=3&fullscreen`.
+    ///
+    /// `--name value` and `--name=value` become `name=value`, flags without a value only keep
+    /// their name and other arguments are passed as `arg=value`.
+    /// Negative numbers like `-5` are values, not flags.
+    pub(crate) fn query_string(&self) -> String {
+        let mut params = Vec::new();
+        let mut args = self.args.iter().peekable();
+
+        while let Some(arg) = args.next() {
+            let Some(name) = flag_name(arg) else {
+                params.push(format!("arg={}", encode_query_component(arg)));
+                continue;
+            };
+
+            if let Some((name, value)) = name.split_once('=') {
+                params.push(format!(
+                    "{}={}",
+                    encode_query_component(name),
+                    encode_query_component(value)
+                ));
+            } else if let Some(value) = args.next_if(|value| flag_name(value).is_none()) {
+                params.push(format!(
+                    "{}={}",
+                    encode_query_component(name),
+                    encode_query_component(value)
+                ));
+            } else {
+                params.push(encode_query_component(name));
+            }
+        }
+
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+}
+
+/// The name of a flag like `--level` or `-l`, or `None` if the argument is a value.
+fn flag_name(arg: &str) -> Option<&str> {
+    arg.strip_prefix("--")
+        .or_else(|| arg.strip_prefix('-'))
+        .filter(|name| !name.is_empty() && !name.starts_with(|char: char| char.is_ascii_digit()))
+}
+
+/// Percent-encode everything except the unreserved characters of URLs.
+fn encode_query_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_args(args: &[&str]) -> AppArgs {
+        AppArgs {
+            args: args.iter().map(|arg| (*arg).to_owned()).collect(),
+            env: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn should_convert_args_to_query_string() {
+        assert_eq!(
+            app_args(&[
+                "save.ron",
+                "--level",
+                "3",
+                "--name=Jane Doe",
+                "--fullscreen"
+            ])
+            .query_string(),
+            "?arg=save.ron&level=3&name=Jane%20Doe&fullscreen"
+        );
+        assert_eq!(app_args(&[]).query_string(), "");
+    }
+
+    #[test]
+    fn should_treat_negative_numbers_as_values() {
+        assert_eq!(
+            app_args(&["--offset", "-5", "-x", "-1.5", "-3", "--verbose"]).query_string(),
+            "?offset=-5&x=-1.5&arg=-3&verbose"
+        );
+    }
+
+    #[test]
+    fn should_inject_global_into_head() {
+        let args = AppArgs {
+            args: vec!["--level".to_owned(), "</script>".to_owned()],
+            env: BTreeMap::from([("RUST_LOG".to_owned(), "debug".to_owned())]),
+        };
+
+        assert_eq!(
+            args.inject("<head><title>App</title></head><body></body>"),
+            r#"<head><title>App</title><script>window.bevyCli = {"args":["--level","<\/script>"],"env":{"RUST_LOG":"debug"}};</script></head><body></body>"#
+        );
+    }
+
+    #[test]
+    fn should_not_change_index_without_args() {
+        let index = "<head></head>";
+        assert_eq!(AppArgs::default().inject(index), index);
+    }
+}
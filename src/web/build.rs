@@ -6,14 +6,18 @@ use fs_extra::dir::{self, CopyOptions};
 use tracing::info;
 
 use super::bundle::WebBundle;
+#[cfg(feature = "archive")]
+use crate::archive::{create_archive, default_archive_path};
 use crate::{
     bin_target::select_run_binary,
     commands::build::{BuildArgs, BuildSubcommands, BuildWebArgs},
     external_cli::{cargo, wasm_bindgen, wasm_opt},
+    profiles::configure_default_web_profiles,
     web::{
         bundle::{PackedBundle, create_web_bundle},
+        compress::compress_bundle,
         getrandom::{apply_getrandom_backend, getrandom_web_feature_config},
-        profiles::configure_default_web_profiles,
+        size_report::report_wasm_size,
     },
 };
 
@@ -25,6 +29,7 @@ use crate::{
 /// - Compiling to Wasm
 /// - Optimizing the Wasm binary (in release mode)
 /// - Creating JavaScript bindings
+/// - Reporting the size of the Wasm binary (if requested)
 /// - Creating a bundled folder (if requested)
 pub fn build_web(args: &mut BuildArgs, metadata: &Metadata) -> anyhow::Result<WebBundle> {
     let bin_target = select_run_binary(
@@ -84,15 +89,29 @@ pub fn build_web(args: &mut BuildArgs, metadata: &Metadata) -> anyhow::Result<We
         wasm_bindgen::bundle(metadata, &bin_target, args.auto_install())?;
         wasm_opt::optimize_path(&bin_target, args.auto_install(), &args.wasm_opt_args())?;
 
+        if web_args.is_some_and(|web_args| web_args.size_report) {
+            report_wasm_size(
+                metadata.target_directory.as_std_path(),
+                args.profile(),
+                &bin_target,
+            )?;
+        }
+
         let web_bundle = create_web_bundle(
             metadata,
             args.profile(),
             &bin_target,
             web_args.is_some_and(|web_args| web_args.create_packed_bundle),
+            web_args.map(BuildWebArgs::file_hashing).unwrap_or_default(),
         )
         .context("failed to create web bundle")?;
 
         if let WebBundle::Packed(PackedBundle { path }) = &web_bundle {
+            if let Some(web_args) = web_args {
+                compress_bundle(path, &web_args.compress)
+                    .context("failed to compress web bundle")?;
+            }
+
             info!("created bundle at file://{}", path.display());
             if let Some(BuildWebArgs {
                 bundle_dir: Some(destination),
@@ -105,6 +124,21 @@ pub fn build_web(args: &mut BuildArgs, metadata: &Metadata) -> anyhow::Result<We
                     .context("failed to copy packed bundle directory to destination directory")?;
                 info!("copied bundle to file://{}", destination.display());
             }
+
+            #[cfg(feature = "archive")]
+            if let Some(BuildWebArgs {
+                archive: Some(format),
+                archive_path,
+                ..
+            }) = web_args
+            {
+                let archive_path = archive_path
+                    .clone()
+                    .unwrap_or_else(|| default_archive_path(path, *format));
+                create_archive(path, &archive_path, *format)
+                    .context("failed to create archive of the bundle")?;
+                info!("created archive at file://{}", archive_path.display());
+            }
         }
 
         Ok(web_bundle)
@@ -8,6 +8,7 @@ use anyhow::Context;
 use cargo_metadata::Metadata;
 use tracing::{info, warn};
 
+use super::cache_busting::{FileHashing, hash_bundle_files};
 use crate::bin_target::BinTarget;
 
 #[derive(Debug, Clone)]
@@ -69,11 +70,14 @@ pub enum WebBundle {
 ///
 /// Otherwise, the assets and build artifacts will be kept at their original place
 /// to avoid duplication.
+///
+/// The files of a packed bundle can get content hashes in their names, see [`FileHashing`].
 pub fn create_web_bundle(
     metadata: &Metadata,
     profile: &str,
     bin_target: &BinTarget,
     packed: bool,
+    hashing: FileHashing,
 ) -> anyhow::Result<WebBundle> {
     let package_root = bin_target
         .package
@@ -81,19 +85,7 @@ pub fn create_web_bundle(
         .parent()
         .context("failed to find package root")?;
 
-    let assets_path = Path::new("assets");
-
-    let package_assets = Path::new(package_root).join(assets_path);
-
-    let assets_path = if package_assets.exists() {
-        info!("using package assets.");
-        Some(package_assets)
-    } else if assets_path.exists() {
-        info!("using workspace assets.");
-        Some(assets_path.into())
-    } else {
-        None
-    };
+    let assets_path = bin_target.assets_path();
 
     // The "_bg" suffix is needed to reference the bindings created by wasm_bindgen,
     // instead of the artifact created directly by cargo.
@@ -232,6 +224,15 @@ pub fn create_web_bundle(
         .context("failed to copy custom web assets")?;
     }
 
+    // Content hashes in file names (optional)
+    let index = hash_bundle_files(
+        base_path.as_std_path(),
+        &bin_target.bin_name,
+        hashing,
+        index,
+    )
+    .context("failed to add content hashes to file names")?;
+
     // Index (pre-processed)
     tracing::debug!("writing index.html");
     fs::write(base_path.join("index.html"), &index)
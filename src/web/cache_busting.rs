@@ -0,0 +1,342 @@
+//! Adding content hashes to the file names of a packed web bundle.
+//!
+//! When the content of a file changes, so does its name.
+//! This allows CDNs and browsers to cache the files indefinitely without serving stale versions
+//! after a new deployment.
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::Serialize;
+use sha2::{Digest as _, Sha384};
+use tracing::{info, warn};
+
+/// The name of the file mapping the original file names to the hashed ones.
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Which files of a packed bundle get a content hash in their name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileHashing {
+    /// Keep the original file names.
+    #[default]
+    Disabled,
+    /// Hash the Wasm binary and its JavaScript bindings.
+    BuildArtifacts,
+    /// Hash the build artifacts and add hashed copies of all files in the assets folder.
+    ///
+    /// The assets keep their original names as well, because Bevy loads them by their path.
+    All,
+}
+
+/// An entry of the bundle manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct ManifestEntry {
+    /// The path of the hashed file, relative to the bundle root.
+    file: String,
+    /// The Subresource Integrity hash of the file content.
+    integrity: String,
+}
+
+/// Rename the build artifacts of the bundle to include content hashes, optionally with hashed
+/// copies of the assets.
+///
+/// The references to the renamed files in the JavaScript bindings and the `index.html` content
+/// are updated, and the index gets Subresource Integrity attributes for the build artifacts.
+/// A `manifest.json` file maps the original file names to the hashed ones.
+///
+/// Returns the updated `index.html` content.
+pub(crate) fn hash_bundle_files(
+    bundle_path: &Path,
+    bin_name: &str,
+    hashing: FileHashing,
+    index: String,
+) -> anyhow::Result<String> {
+    if hashing == FileHashing::Disabled {
+        return Ok(index);
+    }
+
+    let manifest_path = bundle_path.join(MANIFEST_FILE_NAME);
+    if manifest_path.exists() {
+        bail!(
+            "the custom web assets already contain a `{MANIFEST_FILE_NAME}`, which would be overwritten by the bundle manifest"
+        );
+    }
+
+    info!("adding content hashes to file names...");
+
+    let mut manifest = BTreeMap::new();
+    let build_path = bundle_path.join("build");
+
+    // The Wasm binary needs to be hashed first,
+    // because the JS bindings reference it and thereby change their hash
+    let wasm_file_name = format!("{bin_name}_bg.wasm");
+    let (wasm_path, wasm_entry) = hash_file(bundle_path, &build_path.join(&wasm_file_name), true)?;
+
+    let js_file_name = format!("{bin_name}.js");
+    let js_path = build_path.join(&js_file_name);
+    let js = fs::read_to_string(&js_path).context("failed to read JS bindings")?;
+    if !js.contains(&wasm_file_name) {
+        warn!("the JS bindings don't reference `{wasm_file_name}`, the Wasm binary might not load");
+    }
+    fs::write(
+        &js_path,
+        js.replace(&wasm_file_name, &file_name(&wasm_entry.file)),
+    )
+    .context("failed to update JS bindings")?;
+
+    let (js_path, js_entry) = hash_file(bundle_path, &js_path, true)?;
+
+    let index = rewrite_index(index, &[(&wasm_path, &wasm_entry), (&js_path, &js_entry)]);
+
+    manifest.insert(wasm_path, wasm_entry);
+    manifest.insert(js_path, js_entry);
+
+    let assets_path = bundle_path.join("assets");
+    if hashing == FileHashing::All && assets_path.exists() {
+        for asset in files_in(&assets_path).context("failed to read assets")? {
+            let (path, entry) = hash_file(bundle_path, &asset, false)?;
+            manifest.insert(path, entry);
+        }
+    }
+
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .context("failed to write bundle manifest")?;
+
+    Ok(index)
+}
+
+/// Rename or copy the file to include the hash of its content and create its manifest entry.
+///
+/// Returns the original path relative to the bundle root, together with the manifest entry.
+fn hash_file(
+    bundle_path: &Path,
+    path: &Path,
+    rename: bool,
+) -> anyhow::Result<(String, ManifestEntry)> {
+    let content = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let digest = Sha384::digest(&content);
+
+    let hash: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let hashed_path = path.with_file_name(hashed_file_name(path, &hash));
+
+    if rename {
+        fs::rename(path, &hashed_path)
+            .with_context(|| format!("failed to rename {}", path.display()))?;
+    } else {
+        fs::copy(path, &hashed_path)
+            .with_context(|| format!("failed to copy {}", path.display()))?;
+    }
+
+    Ok((
+        bundle_relative_path(bundle_path, path),
+        ManifestEntry {
+            file: bundle_relative_path(bundle_path, &hashed_path),
+            integrity: format!("sha384-{}", BASE64.encode(digest)),
+        },
+    ))
+}
+
+/// Insert the hash before the file extension, e.g. `app_bg.wasm` -> `app_bg.0123456789abcdef.wasm`.
+fn hashed_file_name(path: &Path, hash: &str) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    match path.extension() {
+        Some(extension) => format!("{stem}.{hash}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{hash}"),
+    }
+}
+
+/// Point the index to the hashed build artifacts and add integrity attributes for them.
+///
+/// The artifacts are preloaded with the integrity attributes, because the module imports in the
+/// index can't declare them directly.
+fn rewrite_index(mut index: String, artifacts: &[(&String, &ManifestEntry)]) -> String {
+    let mut preload_links = String::new();
+
+    for (path, entry) in artifacts {
+        let replaced;
+        (index, replaced) = replace_path(&index, path, &entry.file);
+
+        // The Wasm binary is usually only referenced by the JS bindings
+        if !replaced && entry.file.ends_with(".js") {
+            warn!(
+                "the `index.html` doesn't reference `{path}`, update it to load `{}` instead",
+                entry.file
+            );
+        }
+
+        let rel = if entry.file.ends_with(".js") {
+            r#"rel="modulepreload""#
+        } else {
+            r#"rel="preload" as="fetch""#
+        };
+        preload_links += &format!(
+            r#"<link {rel} href="./{}" integrity="{}" crossorigin="anonymous" />"#,
+            entry.file, entry.integrity
+        );
+    }
+
+    index.replacen("</head>", &format!("{preload_links}</head>"), 1)
+}
+
+/// Replace all references to the path, e.g. `./build/app.js`, `/build/app.js` or `build/app.js`.
+///
+/// Only complete paths are replaced, not paths that end or start with the given one.
+/// Returns the updated content and whether the path was found.
+fn replace_path(content: &str, path: &str, replacement: &str) -> (String, bool) {
+    let is_path_char = |char: char| char.is_alphanumeric() || matches!(char, '_' | '-' | '.');
+
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    let mut replaced = false;
+
+    while let Some(position) = rest.find(path) {
+        let (before, after) = (&rest[..position], &rest[position + path.len()..]);
+        // Strip a leading `/` or `./`, but not the parent directory of another path
+        let prefix = before
+            .strip_suffix('/')
+            .map_or(before, |prefix| prefix.strip_suffix('.').unwrap_or(prefix));
+
+        result += before;
+        if !prefix.ends_with(is_path_char) && !after.starts_with(is_path_char) {
+            result += replacement;
+            replaced = true;
+        } else {
+            result += path;
+        }
+        rest = after;
+    }
+    result += rest;
+
+    (result, replaced)
+}
+
+/// All files in the given directory, recursively.
+fn files_in(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            files.extend(files_in(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// The path relative to the bundle root, separated by `/` as in URLs.
+fn bundle_relative_path(bundle_path: &Path, path: &Path) -> String {
+    path.strip_prefix(bundle_path)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The file name of a `/`-separated path.
+fn file_name(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_insert_hash_before_extension() {
+        assert_eq!(
+            hashed_file_name(Path::new("build/app_bg.wasm"), "0123"),
+            "app_bg.0123.wasm"
+        );
+        assert_eq!(
+            hashed_file_name(Path::new("LICENSE"), "0123"),
+            "LICENSE.0123"
+        );
+    }
+
+    #[test]
+    fn should_replace_all_spellings_of_path() {
+        let index = r#"<script src="./build/app.js"></script><script src="/build/app.js"></script>
+<script src="build/app.js"></script><script src="other/build/app.js"></script>
+<script src="build/app.json"></script>"#;
+
+        let (index, replaced) = replace_path(index, "build/app.js", "build/app.0123.js");
+        assert!(replaced);
+        assert_eq!(
+            index,
+            r#"<script src="./build/app.0123.js"></script><script src="/build/app.0123.js"></script>
+<script src="build/app.0123.js"></script><script src="other/build/app.js"></script>
+<script src="build/app.json"></script>"#
+        );
+        assert!(!replace_path("<head></head>", "build/app.js", "build/app.0123.js").1);
+    }
+
+    #[test]
+    fn should_rename_artifacts_and_rewrite_references() -> anyhow::Result<()> {
+        let bundle = tempfile::tempdir()?;
+        let build = bundle.path().join("build");
+        fs::create_dir_all(bundle.path().join("assets").join("textures"))?;
+        fs::create_dir(&build)?;
+        fs::write(build.join("app_bg.wasm"), b"\0asm")?;
+        fs::write(
+            build.join("app.js"),
+            "module_or_path = new URL('app_bg.wasm', import.meta.url);",
+        )?;
+        fs::write(
+            bundle
+                .path()
+                .join("assets")
+                .join("textures")
+                .join("player.png"),
+            b"png",
+        )?;
+
+        let index = hash_bundle_files(
+            bundle.path(),
+            "app",
+            FileHashing::All,
+            r#"<head></head><script type="module">import init from "./build/app.js";</script>"#
+                .to_owned(),
+        )?;
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(bundle.path().join(MANIFEST_FILE_NAME))?)?;
+        let hashed_file = |name: &str| manifest[name]["file"].as_str().unwrap().to_owned();
+
+        let wasm = hashed_file("build/app_bg.wasm");
+        let js = hashed_file("build/app.js");
+        assert!(wasm.starts_with("build/app_bg.") && wasm.ends_with(".wasm"));
+        assert!(!build.join("app.js").exists());
+        // The assets are copied, so that Bevy can still load them by their original path
+        assert!(
+            bundle
+                .path()
+                .join(hashed_file("assets/textures/player.png"))
+                .exists()
+        );
+        assert!(bundle.path().join("assets/textures/player.png").exists());
+
+        // The JS bindings load the hashed Wasm binary
+        assert!(fs::read_to_string(bundle.path().join(&js))?.contains(&file_name(&wasm)));
+
+        // The index imports the hashed JS bindings and declares their integrity
+        assert!(index.contains(&format!(r#"import init from "./{js}";"#)));
+        assert!(index.contains(&format!(
+            r#"<link rel="modulepreload" href="./{js}" integrity="{}""#,
+            manifest["build/app.js"]["integrity"].as_str().unwrap()
+        )));
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! Pre-compressing the files of a packed web bundle.
+//!
+//! Web servers and CDNs can serve the compressed files directly to browsers that support them,
+//! which drastically reduces the download size of the Wasm binary.
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use clap::ValueEnum;
+use tracing::info;
+
+/// The compression algorithms that can be used to pre-compress the bundle.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    /// Create `.gz` files, supported by all browsers.
+    Gzip,
+    /// Create `.br` files, which are usually smaller than gzip.
+    Brotli,
+}
+
+impl CompressionFormat {
+    /// The file extension appended to the compressed files.
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Brotli => "br",
+        }
+    }
+
+    /// Compress the file at `source`, writing the result to `destination`.
+    fn compress(self, source: &Path, destination: &Path) -> io::Result<()> {
+        let mut reader = BufReader::new(File::open(source)?);
+        let writer = BufWriter::new(File::create(destination)?);
+
+        match self {
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(writer, flate2::Compression::best());
+                io::copy(&mut reader, &mut encoder)?;
+                encoder.finish()?.flush()
+            }
+            Self::Brotli => {
+                // Maximum quality with a 4 MiB window, as used by most CDNs for static files
+                let mut encoder = brotli::CompressorWriter::new(writer, 4096, 11, 22);
+                io::copy(&mut reader, &mut encoder)?;
+                encoder.flush()
+            }
+        }
+    }
+}
+
+/// The sizes of the compressed bundle files.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct CompressionSummary {
+    /// The total size of the uncompressed files.
+    original_size: u64,
+    /// The total size of the compressed files.
+    ///
+    /// Files where compression doesn't reduce the size are counted with their original size,
+    /// because they are served uncompressed.
+    compressed_size: u64,
+}
+
+/// Create compressed siblings for every file in the bundle, e.g. `app_bg.wasm.br`.
+///
+/// Compressed files that are not smaller than the original are discarded,
+/// which is usually the case for already compressed assets like images and audio.
+pub(crate) fn compress_bundle(
+    bundle_path: &Path,
+    formats: &[CompressionFormat],
+) -> anyhow::Result<()> {
+    if formats.is_empty() {
+        return Ok(());
+    }
+
+    let files = bundle_files(bundle_path).context("failed to read bundle directory")?;
+
+    for &format in formats {
+        info!("compressing bundle with {format:?}...");
+        let mut summary = CompressionSummary::default();
+
+        for file in &files {
+            let original_size = fs::metadata(file)?.len();
+            let compressed_path = compressed_path(file, format);
+
+            format
+                .compress(file, &compressed_path)
+                .with_context(|| format!("failed to compress {}", file.display()))?;
+
+            let compressed_size = fs::metadata(&compressed_path)?.len();
+
+            let served_size = if compressed_size < original_size {
+                compressed_size
+            } else {
+                fs::remove_file(&compressed_path)?;
+                original_size
+            };
+
+            if is_build_artifact(file) {
+                info!(
+                    "{}: {} -> {} ({})",
+                    file.strip_prefix(bundle_path).unwrap_or(file).display(),
+                    format_size(original_size),
+                    format_size(served_size),
+                    format.extension()
+                );
+            }
+
+            summary.original_size += original_size;
+            summary.compressed_size += served_size;
+        }
+
+        info!(
+            "total bundle size: {} -> {} ({})",
+            format_size(summary.original_size),
+            format_size(summary.compressed_size),
+            format.extension()
+        );
+    }
+
+    Ok(())
+}
+
+/// All files in the bundle, excluding previously compressed files.
+fn bundle_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            files.extend(bundle_files(&path)?);
+        } else if !is_compressed_sibling(&path) {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// The path of the compressed sibling of the given file, e.g. `app.js` -> `app.js.gz`.
+fn compressed_path(path: &Path, format: CompressionFormat) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".");
+    file_name.push(format.extension());
+    path.with_file_name(file_name)
+}
+
+/// Whether the file has been created by a previous compression.
+fn is_compressed_sibling(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "gz" || extension == "br")
+}
+
+/// Whether the file is the Wasm binary or its JavaScript bindings,
+/// which are reported individually.
+fn is_build_artifact(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "wasm" || extension == "js")
+        && path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|parent| parent == "build")
+}
+
+/// Format a file size in a human-readable way, e.g. `12.34 MiB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.2} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_format_sizes() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.00 KiB");
+        assert_eq!(format_size(30 * 1024 * 1024 + 512 * 1024), "30.50 MiB");
+    }
+
+    #[test]
+    fn should_create_compressed_siblings() -> anyhow::Result<()> {
+        let bundle = tempfile::tempdir()?;
+        let build = bundle.path().join("build");
+        fs::create_dir(&build)?;
+        fs::write(build.join("app.js"), "console.log('hello');\n".repeat(100))?;
+        // Random-looking data doesn't compress well
+        fs::write(bundle.path().join("noise.bin"), [0x8f, 0x1a, 0x33])?;
+
+        compress_bundle(
+            bundle.path(),
+            &[CompressionFormat::Gzip, CompressionFormat::Brotli],
+        )?;
+
+        assert!(build.join("app.js.gz").exists());
+        assert!(build.join("app.js.br").exists());
+        assert!(!bundle.path().join("noise.bin.gz").exists());
+        assert!(!bundle.path().join("noise.bin.br").exists());
+
+        // Compressing again doesn't compress the compressed files
+        compress_bundle(bundle.path(), &[CompressionFormat::Gzip])?;
+        assert!(!build.join("app.js.gz.gz").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+//! Serving the app over HTTPS, which is required for secure contexts on other devices.
+use std::{
+    fs, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use axum::serve::Listener;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{ServerConfig, crypto::ring},
+    server::TlsStream,
+};
+use tracing::{debug, info, warn};
+
+/// The certificate to use for serving the app over HTTPS.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Certificate {
+    /// Generate a self-signed certificate, which is cached in the target directory.
+    SelfSigned,
+    /// Use a certificate and private key provided by the user, in the PEM format.
+    Files { cert: PathBuf, key: PathBuf },
+}
+
+/// Create the TLS configuration for the web server.
+///
+/// Self-signed certificates are stored in the target directory, so that browsers only need to
+/// trust them once.
+pub(crate) fn tls_config(
+    certificate: &Certificate,
+    target_directory: &Path,
+    host: &str,
+) -> anyhow::Result<Arc<ServerConfig>> {
+    let (cert_path, key_path) = match certificate {
+        Certificate::SelfSigned => self_signed_certificate(target_directory, host)?,
+        Certificate::Files { cert, key } => (cert.clone(), key.clone()),
+    };
+
+    let cert_chain = CertificateDer::pem_file_iter(&cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("failed to read certificate {}", cert_path.display()))?;
+    let key = PrivateKeyDer::from_pem_file(&key_path)
+        .with_context(|| format!("failed to read private key {}", key_path.display()))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)
+        .context("invalid certificate or private key")?;
+    // The server only supports HTTP/1.1
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(Arc::new(config))
+}
+
+/// Get the paths to the self-signed certificate and its private key for the given host.
+///
+/// The certificate is only generated if it doesn't exist yet.
+fn self_signed_certificate(
+    target_directory: &Path,
+    host: &str,
+) -> anyhow::Result<(PathBuf, PathBuf)> {
+    // When binding to an unspecified address like `0.0.0.0`, the app is reached via the address
+    // of the machine in the local network, e.g. from a phone
+    let local_address = host
+        .parse::<IpAddr>()
+        .ok()
+        .filter(IpAddr::is_unspecified)
+        .and_then(local_network_address);
+
+    // The certificate must be regenerated when the address in the local network changes
+    let directory_name = match local_address {
+        Some(local_address) => format!("{host}_{local_address}"),
+        None => host.to_owned(),
+    };
+    let directory = target_directory
+        .join("bevy_web")
+        .join("https")
+        .join(directory_name.replace(':', "_"));
+    let cert_path = directory.join("cert.pem");
+    let key_path = directory.join("key.pem");
+
+    if cert_path.exists() && key_path.exists() {
+        debug!(
+            "using cached certificate from file://{}",
+            cert_path.display()
+        );
+        return Ok((cert_path, key_path));
+    }
+
+    info!("generating self-signed certificate...");
+    let subject_alt_names = subject_alt_names(host, local_address);
+    debug!("the certificate is valid for {subject_alt_names:?}");
+
+    let certified_key = rcgen::generate_simple_self_signed(subject_alt_names)
+        .context("failed to generate self-signed certificate")?;
+
+    fs::create_dir_all(&directory).context("failed to create certificate directory")?;
+    fs::write(&cert_path, certified_key.cert.pem()).context("failed to write certificate")?;
+    fs::write(&key_path, certified_key.signing_key.serialize_pem())
+        .context("failed to write private key")?;
+
+    info!("stored certificate at file://{}", cert_path.display());
+    warn!(
+        "the certificate is self-signed, so your browser will show a security warning when opening the app for the first time"
+    );
+
+    Ok((cert_path, key_path))
+}
+
+/// The names and addresses the self-signed certificate is valid for.
+fn subject_alt_names(host: &str, local_address: Option<IpAddr>) -> Vec<String> {
+    let mut subject_alt_names = vec![
+        "localhost".to_owned(),
+        "127.0.0.1".to_owned(),
+        "::1".to_owned(),
+    ];
+    if !subject_alt_names.iter().any(|name| name == host) {
+        subject_alt_names.push(host.to_owned());
+    }
+
+    if let Some(local_address) = local_address {
+        subject_alt_names.push(local_address.to_string());
+    }
+
+    subject_alt_names
+}
+
+/// The address of the machine in the local network, of the same IP version as the given address.
+///
+/// Connecting a UDP socket doesn't send any packets, but selects the interface that would be used
+/// to reach a public address.
+fn local_network_address(unspecified: IpAddr) -> Option<IpAddr> {
+    let public_address: IpAddr = match unspecified {
+        IpAddr::V4(_) => Ipv4Addr::new(192, 0, 2, 1).into(),
+        IpAddr::V6(_) => Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).into(),
+    };
+
+    let socket = UdpSocket::bind(SocketAddr::new(unspecified, 0)).ok()?;
+    socket.connect(SocketAddr::new(public_address, 80)).ok()?;
+    let address = socket.local_addr().ok()?.ip();
+
+    (!address.is_loopback() && !address.is_unspecified()).then_some(address)
+}
+
+/// A listener accepting TLS connections, to be used with [`axum::serve`].
+///
+/// The TLS handshakes are performed in separate tasks,
+/// so that a slow client cannot block other connections.
+pub(crate) struct TlsListener {
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    pub(crate) fn new(listener: TcpListener, config: Arc<ServerConfig>) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let acceptor = TlsAcceptor::from(config);
+        let (sender, connections) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    // E.g. too many open files, retrying immediately would just fail again
+                    Err(error) => {
+                        warn!("failed to accept connection: {error}");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+
+                tokio::spawn(async move {
+                    match acceptor.accept(stream).await {
+                        Ok(stream) => {
+                            let _ = sender.send((stream, addr)).await;
+                        }
+                        // This happens e.g. when the browser doesn't trust the certificate yet
+                        Err(error) => debug!("TLS handshake with {addr} failed: {error}"),
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            connections,
+            local_addr,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        // The sender lives as long as the accepting task, which never terminates
+        self.connections
+            .recv()
+            .await
+            .expect("TLS connection channel closed unexpectedly")
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_and_reuse_self_signed_certificate() -> anyhow::Result<()> {
+        let target_directory = tempfile::tempdir()?;
+
+        let (cert_path, key_path) = self_signed_certificate(target_directory.path(), "127.0.0.1")?;
+        let cert = fs::read_to_string(&cert_path)?;
+
+        assert!(cert.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(key_path.exists());
+        // The cached certificate is reused
+        assert_eq!(
+            self_signed_certificate(target_directory.path(), "127.0.0.1")?,
+            (cert_path.clone(), key_path)
+        );
+        assert_eq!(fs::read_to_string(&cert_path)?, cert);
+
+        // The generated certificate can be loaded by the server
+        tls_config(
+            &Certificate::SelfSigned,
+            target_directory.path(),
+            "127.0.0.1",
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn should_include_local_network_address() {
+        assert_eq!(
+            subject_alt_names("0.0.0.0", Some(IpAddr::from([192, 168, 0, 42]))),
+            ["localhost", "127.0.0.1", "::1", "0.0.0.0", "192.168.0.42"]
+        );
+        assert_eq!(
+            subject_alt_names("192.168.0.7", None),
+            ["localhost", "127.0.0.1", "::1", "192.168.0.7"]
+        );
+        assert_eq!(
+            subject_alt_names("127.0.0.1", None),
+            ["localhost", "127.0.0.1", "::1"]
+        );
+    }
+}
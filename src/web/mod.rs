@@ -1,10 +1,17 @@
 //! Utilities for building and running the app in the browser.
 
+pub(crate) mod app_args;
 pub(crate) mod build;
 pub(crate) mod bundle;
+pub(crate) mod cache_busting;
+pub(crate) mod compress;
 pub(crate) mod getrandom;
-pub(crate) mod profiles;
+#[cfg(feature = "https")]
+pub(crate) mod https;
+pub(crate) mod proxy;
 pub(crate) mod run;
 pub(crate) mod serve;
+pub(crate) mod size_report;
 #[cfg(feature = "unstable")]
 pub(crate) mod unstable;
+pub(crate) mod watch;
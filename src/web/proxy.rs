@@ -0,0 +1,257 @@
+//! Forwarding requests from the dev server to other servers, e.g. a local backend.
+//!
+//! This makes the app and the backend appear as a single origin in the browser,
+//! so that the requests are not blocked as cross-origin.
+use std::{cmp::Reverse, str::FromStr, sync::Arc};
+
+use anyhow::{Context as _, bail};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{StatusCode, Uri, header};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+use tracing::{debug, warn};
+
+/// Forward all requests with the given path prefix to an upstream server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProxyRule {
+    /// The path prefix to match, e.g. `/api`.
+    prefix: String,
+    /// The server to forward the requests to, e.g. `http://127.0.0.1:8080`.
+    upstream: Uri,
+}
+
+impl ProxyRule {
+    /// Whether the request path should be forwarded by this rule.
+    ///
+    /// Only full path segments are matched, so `/api` matches `/api/users` but not `/apis`.
+    fn matches(&self, path: &str) -> bool {
+        path.strip_prefix(&self.prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    /// The URI to request from the upstream server, in origin form (i.e. only path and query).
+    ///
+    /// The full request path is appended to the path of the upstream URI.
+    fn upstream_uri(&self, uri: &Uri) -> anyhow::Result<Uri> {
+        let base_path = self.upstream.path().trim_end_matches('/');
+        let path_and_query = uri
+            .path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or("/");
+
+        format!("{base_path}{path_and_query}")
+            .parse()
+            .context("failed to build upstream URI")
+    }
+
+    /// The host and port of the upstream server.
+    fn authority(&self) -> &str {
+        self.upstream
+            .authority()
+            .map(|authority| authority.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The address to connect to, using the default HTTP port if none is specified.
+    fn address(&self) -> String {
+        let host = self.upstream.host().unwrap_or_default();
+        let port = self.upstream.port_u16().unwrap_or(80);
+        format!("{host}:{port}")
+    }
+}
+
+impl FromStr for ProxyRule {
+    type Err = anyhow::Error;
+
+    /// Parse a rule in the format `/path=http://host:port`.
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let Some((prefix, upstream)) = rule.split_once('=') else {
+            bail!("proxy rules must be in the format `/path=http://host:port`, got `{rule}`");
+        };
+
+        let prefix = prefix.trim().trim_end_matches('/');
+        if !prefix.starts_with('/') {
+            bail!("the proxied path `{prefix}` must start with `/` and must not be the root path");
+        }
+
+        let upstream: Uri = upstream
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid proxy URL `{upstream}`"))?;
+        if !matches!(upstream.scheme_str(), Some("http" | "ws")) || upstream.host().is_none() {
+            bail!("the proxy URL `{upstream}` must be an `http://` or `ws://` URL with a host");
+        }
+
+        Ok(Self {
+            prefix: prefix.to_owned(),
+            upstream,
+        })
+    }
+}
+
+/// Parse the proxy rules, where earlier rules take precedence over later rules with the same path.
+///
+/// This allows rules passed via the CLI to override the rules from the config.
+pub(crate) fn parse_proxy_rules<'a>(
+    rules: impl Iterator<Item = &'a String>,
+) -> anyhow::Result<Vec<ProxyRule>> {
+    let mut proxy_rules: Vec<ProxyRule> = Vec::new();
+
+    for rule in rules {
+        let rule: ProxyRule = rule.parse()?;
+
+        if proxy_rules
+            .iter()
+            .all(|existing| existing.prefix != rule.prefix)
+        {
+            proxy_rules.push(rule);
+        }
+    }
+
+    // Match the most specific prefix first, e.g. `/api/v2` before `/api`
+    proxy_rules.sort_by_key(|rule| Reverse(rule.prefix.len()));
+
+    Ok(proxy_rules)
+}
+
+/// Middleware forwarding matching requests to the upstream server of the proxy rule.
+///
+/// All other requests are handled by the dev server itself.
+pub(crate) async fn proxy_middleware(
+    State(rules): State<Arc<[ProxyRule]>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(rule) = rules.iter().find(|rule| rule.matches(request.uri().path())) else {
+        return next.run(request).await;
+    };
+
+    let uri = request.uri().clone();
+
+    match forward(rule, request).await {
+        Ok(response) => response,
+        Err(error) => {
+            warn!("failed to forward {uri} to {}: {error:?}", rule.upstream);
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("failed to forward request to {}: {error}", rule.upstream),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Forward the request to the upstream server and return its response.
+///
+/// Connection upgrades, such as websockets, are supported by piping both connections together.
+async fn forward(rule: &ProxyRule, mut request: Request) -> anyhow::Result<Response> {
+    let upstream_uri = rule.upstream_uri(request.uri())?;
+    debug!(
+        "forwarding {} to http://{}{upstream_uri}",
+        request.uri(),
+        rule.authority()
+    );
+
+    let stream = TcpStream::connect(rule.address())
+        .await
+        .with_context(|| format!("failed to connect to {}", rule.address()))?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .context("failed to establish HTTP connection")?;
+    tokio::spawn(async move {
+        if let Err(error) = connection.with_upgrades().await {
+            debug!("proxy connection closed: {error}");
+        }
+    });
+
+    let client_upgrade = request
+        .headers()
+        .contains_key(header::UPGRADE)
+        .then(|| hyper::upgrade::on(&mut request));
+
+    *request.uri_mut() = upstream_uri;
+    request.headers_mut().insert(
+        header::HOST,
+        rule.authority().parse().context("invalid upstream host")?,
+    );
+
+    let mut response = sender
+        .send_request(request)
+        .await
+        .context("failed to send request")?;
+
+    if response.status() == StatusCode::SWITCHING_PROTOCOLS
+        && let Some(client_upgrade) = client_upgrade
+    {
+        let upstream_upgrade = hyper::upgrade::on(&mut response);
+
+        tokio::spawn(async move {
+            let (Ok(client), Ok(upstream)) = (client_upgrade.await, upstream_upgrade.await) else {
+                debug!("failed to upgrade proxied connection");
+                return;
+            };
+
+            let _ = tokio::io::copy_bidirectional(
+                &mut TokioIo::new(client),
+                &mut TokioIo::new(upstream),
+            )
+            .await;
+        });
+    }
+
+    Ok(response.map(Body::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_proxy_rule() -> anyhow::Result<()> {
+        let rule: ProxyRule = "/api/=http://127.0.0.1:8080/v1/".parse()?;
+
+        assert_eq!(rule.prefix, "/api");
+        assert_eq!(rule.address(), "127.0.0.1:8080");
+        assert!(rule.matches("/api"));
+        assert!(rule.matches("/api/users"));
+        assert!(!rule.matches("/apis"));
+        assert_eq!(
+            rule.upstream_uri(&Uri::from_static("/api/users?id=1"))?,
+            Uri::from_static("/v1/api/users?id=1")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_invalid_proxy_rules() {
+        assert!("/api".parse::<ProxyRule>().is_err());
+        assert!("/=http://127.0.0.1:8080".parse::<ProxyRule>().is_err());
+        assert!("/api=https://example.com".parse::<ProxyRule>().is_err());
+    }
+
+    #[test]
+    fn should_prefer_earlier_and_more_specific_rules() -> anyhow::Result<()> {
+        let rules = parse_proxy_rules(
+            [
+                "/api=http://127.0.0.1:8080".to_owned(),
+                "/api/v2=http://127.0.0.1:9090".to_owned(),
+                "/api=http://127.0.0.1:1234".to_owned(),
+            ]
+            .iter(),
+        )?;
+
+        assert_eq!(
+            rules,
+            vec![
+                "/api/v2=http://127.0.0.1:9090".parse()?,
+                "/api=http://127.0.0.1:8080".parse()?,
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
+    collections::BTreeMap,
     net::{IpAddr, SocketAddr},
     str::FromStr,
+    thread,
 };
 
 use anyhow::Context as _;
@@ -8,16 +10,37 @@ use cargo_metadata::Metadata;
 use http::{HeaderMap, HeaderValue};
 use tracing::{error, info};
 
-use super::{build::build_web, serve::serve};
-use crate::commands::{
-    build::BuildArgs,
-    run::{RunArgs, RunSubcommands, RunWebArgs},
+#[cfg(feature = "https")]
+use super::https::tls_config;
+use super::{
+    app_args::AppArgs,
+    build::build_web,
+    bundle::{LinkedBundle, WebBundle},
+    proxy::parse_proxy_rules,
+    serve::{DevServer, serve},
+    watch::{watch_and_rebuild, watch_assets, watched_paths},
+};
+use crate::{
+    bin_target::select_run_binary,
+    commands::{
+        build::BuildArgs,
+        run::{RunArgs, RunSubcommands, RunWebArgs},
+    },
 };
 
 /// Run the app in the browser.
 ///
 /// Requires [`RunSubcommands::Web`] to be defined.
-pub(crate) fn run_web(args: &mut RunArgs, metadata: &Metadata) -> anyhow::Result<()> {
+///
+/// The arguments after `--` and the environment variables from the config are passed to the app
+/// via the `index.html` and the URL of the page.
+pub(crate) fn run_web(
+    args: &mut RunArgs,
+    metadata: &Metadata,
+    env: BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    // Keep the original arguments around to start rebuilds from a clean state
+    let original_args = args.clone();
     let mut build_args: BuildArgs = args.clone().into();
 
     let web_args = match &mut args.subcommand {
@@ -37,13 +60,70 @@ pub(crate) fn run_web(args: &mut RunArgs, metadata: &Metadata) -> anyhow::Result
     }
 
     let header_map = parse_headers(web_args.headers.iter())?;
+    let proxy_rules = parse_proxy_rules(web_args.proxy.iter())?;
 
     let web_bundle = build_web(&mut build_args, metadata)?;
 
-    let port = web_args.port;
-    let host = IpAddr::from_str(&web_args.host).context("failed to parse host address")?;
+    let app_args = AppArgs {
+        args: args.forward_args.clone(),
+        env,
+    };
+    let query_string = app_args.query_string();
+    let dev_server = DevServer::new(app_args);
+
+    if web_args.watch {
+        let bin_target = select_run_binary(
+            metadata,
+            build_args.cargo_args.package_args.package.as_deref(),
+            build_args.cargo_args.target_args.bin.as_deref(),
+            build_args.cargo_args.target_args.example.as_deref(),
+            build_args.target().as_deref(),
+            build_args.profile(),
+        )?;
+        let paths = watched_paths(metadata, &bin_target, &web_bundle);
+        let metadata = metadata.clone();
+        let dev_server = dev_server.clone();
+
+        // Assets don't require a rebuild, the browser is notified about them instead
+        if let WebBundle::Linked(LinkedBundle {
+            assets_path: Some(assets_path),
+            ..
+        }) = &web_bundle
+        {
+            let assets_path = assets_path.clone();
+            let dev_server = dev_server.clone();
+            thread::spawn(move || watch_assets(assets_path, dev_server));
+        }
+
+        info!("watching for changes...");
+        thread::spawn(move || watch_and_rebuild(original_args, metadata, paths, dev_server));
+    }
+
+    let port = web_args.port();
+    let host = IpAddr::from_str(web_args.host()).context("failed to parse host address")?;
     let address = SocketAddr::new(host, port);
-    let url = format!("http://{address}");
+
+    #[cfg(feature = "https")]
+    let tls_config = web_args
+        .https_certificate()
+        .map(|certificate| {
+            tls_config(
+                &certificate,
+                metadata.target_directory.as_std_path(),
+                web_args.host(),
+            )
+        })
+        .transpose()?;
+    #[cfg(feature = "https")]
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    #[cfg(not(feature = "https"))]
+    let scheme = "http";
+
+    let url = format!("{scheme}://{address}/{query_string}");
 
     // Serving the app is blocking, so we open the page first
     if web_args.open {
@@ -59,7 +139,15 @@ pub(crate) fn run_web(args: &mut RunArgs, metadata: &Metadata) -> anyhow::Result
         info!("open your app at <{url}>!");
     }
 
-    serve(web_bundle, address, header_map)?;
+    serve(
+        web_bundle,
+        address,
+        header_map,
+        proxy_rules,
+        dev_server,
+        #[cfg(feature = "https")]
+        tls_config,
+    )?;
 
     Ok(())
 }
@@ -1,5 +1,10 @@
 //! Serving the app locally for the browser.
-use std::net::SocketAddr;
+use std::{
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
 
 use axum::{
     Router,
@@ -7,28 +12,174 @@ use axum::{
         WebSocketUpgrade,
         ws::{Message, WebSocket},
     },
-    middleware::map_response,
-    response::Response,
+    middleware::{from_fn_with_state, map_response},
+    response::{IntoResponse, Response},
     routing::{any, get, get_service},
 };
-use http::HeaderMap;
+use http::{HeaderMap, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
 use tower_http::{
     services::{ServeDir, ServeFile},
     trace::TraceLayer,
 };
 
-use crate::web::bundle::{Index, LinkedBundle, PackedBundle, WebBundle};
+#[cfg(feature = "https")]
+use crate::web::https::TlsListener;
+use crate::web::{
+    app_args::AppArgs,
+    bundle::{Index, LinkedBundle, PackedBundle, WebBundle},
+    proxy::{ProxyRule, proxy_middleware},
+};
+
+/// A message sent from the dev server to the browser via the dev websocket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum DevServerMessage {
+    /// The app has been rebuilt, the page should be reloaded.
+    Reload,
+    /// The app failed to build, the error should be displayed on the page.
+    BuildError { message: String },
+    /// A file in the assets folder has been added or modified.
+    AssetChanged {
+        /// The path of the asset, relative to the assets folder.
+        path: String,
+        /// The time of the last modification, in milliseconds since the Unix epoch.
+        modified: u64,
+        /// The hex-encoded SHA-256 hash of the new file content.
+        hash: String,
+    },
+}
+
+/// A message sent from the browser to the dev server via the dev websocket.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum BrowserMessage {
+    /// Output of the `console` or an uncaught error in the browser.
+    Log {
+        level: BrowserLogLevel,
+        message: String,
+    },
+}
+
+/// The level of a message logged in the browser, corresponding to the `console` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum BrowserLogLevel {
+    Error,
+    Warn,
+    Info,
+    Log,
+    Debug,
+    Trace,
+}
+
+impl BrowserMessage {
+    /// Print the message in the terminal, using the same formatting as the CLI output.
+    fn log(&self) {
+        match self {
+            Self::Log { level, message } => match level {
+                BrowserLogLevel::Error => tracing::error!("[browser] {message}"),
+                BrowserLogLevel::Warn => tracing::warn!("[browser] {message}"),
+                BrowserLogLevel::Info | BrowserLogLevel::Log => {
+                    tracing::info!("[browser] {message}");
+                }
+                BrowserLogLevel::Debug => tracing::debug!("[browser] {message}"),
+                BrowserLogLevel::Trace => tracing::trace!("[browser] {message}"),
+            },
+        }
+    }
+}
 
-async fn dev_websocket(ws: WebSocketUpgrade) -> Response {
-    ws.on_upgrade(handle_socket)
+/// State shared between the web server and the build process.
+///
+/// Allows to notify the browser about rebuilds without restarting the server.
+#[derive(Debug, Clone)]
+pub(crate) struct DevServer {
+    /// Messages to send to all connected browsers.
+    messages: broadcast::Sender<DevServerMessage>,
+    /// The (pre-processed) content of the `index.html` file to serve.
+    index: Arc<RwLock<String>>,
+    /// The arguments and environment variables to inject into the `index.html`.
+    app_args: Arc<AppArgs>,
 }
 
-async fn handle_socket(mut socket: WebSocket) {
-    while let Some(Ok(msg)) = socket.recv().await {
-        if let Message::Text(msg) = msg
-            && socket.send(Message::Text(msg)).await.is_err()
+impl DevServer {
+    pub(crate) fn new(app_args: AppArgs) -> Self {
+        let (messages, _) = broadcast::channel(16);
+
+        Self {
+            messages,
+            index: Arc::default(),
+            app_args: Arc::new(app_args),
+        }
+    }
+
+    /// Send a message to all connected browsers.
+    pub(crate) fn send(&self, message: DevServerMessage) {
+        // An error only means that no browser is connected at the moment, which is fine
+        let _ = self.messages.send(message);
+    }
+
+    /// Serve the files of the updated web bundle.
+    ///
+    /// The build artifacts are read from disk on every request, so only the index needs to be
+    /// updated.
+    pub(crate) fn update_bundle(&self, web_bundle: &WebBundle) {
+        if let WebBundle::Linked(LinkedBundle {
+            index: Index::Content(content),
+            ..
+        }) = web_bundle
         {
-            break;
+            // Try to inject the auto reload script in the document body
+            // TODO: Do this also for the other cases when the `index.html` is in a
+            // folder
+            let content = content.replace(
+                "</body>",
+                r#"<script src="_bevy_dev/auto_reload.js"></script></body>"#,
+            );
+            let content = self.app_args.inject(&content);
+
+            *self.index.write().expect("index lock poisoned") = content;
+        }
+    }
+}
+
+async fn dev_websocket(ws: WebSocketUpgrade, dev_server: DevServer) -> Response {
+    let messages = dev_server.messages.subscribe();
+    ws.on_upgrade(move |socket| handle_socket(socket, messages))
+}
+
+async fn handle_socket(mut socket: WebSocket, mut messages: broadcast::Receiver<DevServerMessage>) {
+    loop {
+        tokio::select! {
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Text(msg))) => {
+                    match serde_json::from_str::<BrowserMessage>(&msg) {
+                        Ok(msg) => msg.log(),
+                        Err(error) => {
+                            tracing::debug!("received unknown message from the browser: {error}");
+                        }
+                    }
+                }
+                Some(Ok(_)) => {}
+                // The browser disconnected
+                Some(Err(_)) | None => break,
+            },
+            message = messages.recv() => match message {
+                Ok(message) => {
+                    let Ok(message) = serde_json::to_string(&message) else {
+                        continue;
+                    };
+
+                    if socket.send(Message::Text(message.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // Outdated messages can be skipped
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
         }
     }
 }
@@ -39,6 +190,9 @@ pub(crate) async fn serve(
     web_bundle: WebBundle,
     addr: SocketAddr,
     header_map: HeaderMap,
+    proxy_rules: Vec<ProxyRule>,
+    dev_server: DevServer,
+    #[cfg(feature = "https")] tls_config: Option<Arc<tokio_rustls::rustls::ServerConfig>>,
 ) -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
 
@@ -56,7 +210,16 @@ pub(crate) async fn serve(
             // Using `fallback_service` instead of `route_service`
             // to recursively serve the directory with correct MIME types
             tracing::debug!("Serving packed bundle from {path:?}");
-            router = router.fallback_service(get_service(ServeDir::new(path)));
+            // Pre-compressed files are served if the browser supports them
+            if !dev_server.app_args.is_empty() {
+                let index_path = path.join("index.html");
+                router = router
+                    .route("/", serve_index_file(index_path.clone(), &dev_server))
+                    .route("/index.html", serve_index_file(index_path, &dev_server));
+            }
+            router = router.fallback_service(get_service(
+                ServeDir::new(path).precompressed_br().precompressed_gzip(),
+            ));
         }
         WebBundle::Linked(LinkedBundle {
             build_artifact_path,
@@ -81,8 +244,13 @@ pub(crate) async fn serve(
                     }),
                 )
                 // Open a websocket for automatic reloading
-                // For now, just echo the messages back
-                .route("/_bevy_dev/websocket", any(dev_websocket));
+                .route(
+                    "/_bevy_dev/websocket",
+                    any({
+                        let dev_server = dev_server.clone();
+                        move |ws| dev_websocket(ws, dev_server)
+                    }),
+                );
 
             // If the app has an assets folder, serve it under `/assets`
             if let Some(assets_path) = assets_path {
@@ -90,21 +258,20 @@ pub(crate) async fn serve(
             }
 
             match index {
+                Index::File(path) if !dev_server.app_args.is_empty() => {
+                    router = router.route("/", serve_index_file(path, &dev_server));
+                }
                 Index::File(path) => {
                     router = router.route_service("/", ServeFile::new(path));
                 }
-                Index::Content(content) => {
-                    // Try to inject the auto reload script in the document body
-                    // TODO: Do this also for the other cases when the `index.html` is in a
-                    // folder
-                    let contents = content.replace(
-                        "</body>",
-                        r#"<script src="_bevy_dev/auto_reload.js"></script></body>"#,
-                    );
+                Index::Content(_) => {
+                    dev_server.update_bundle(&web_bundle);
+                    let index = dev_server.index.clone();
 
                     router = router.route(
                         "/",
-                        get(async || {
+                        get(async move || {
+                            let contents = index.read().expect("index lock poisoned").clone();
                             (
                                 [(http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
                                 contents,
@@ -126,6 +293,17 @@ pub(crate) async fn serve(
         }
     }
 
+    // Forward matching requests to other servers, taking precedence over all other routes
+    if !proxy_rules.is_empty() {
+        for rule in &proxy_rules {
+            tracing::debug!("proxying {rule:?}");
+        }
+        router = router.layer(from_fn_with_state(
+            Arc::<[ProxyRule]>::from(proxy_rules),
+            proxy_middleware,
+        ));
+    }
+
     // Add middlewares
     router = router
         .layer(TraceLayer::new_for_http())
@@ -138,7 +316,64 @@ pub(crate) async fn serve(
             response
         }));
 
+    #[cfg(feature = "https")]
+    if let Some(tls_config) = tls_config {
+        let listener = TlsListener::new(listener, tls_config)?;
+        axum::serve(listener, router).await?;
+        return Ok(());
+    }
+
     axum::serve(listener, router).await.unwrap();
 
     Ok(())
 }
+
+/// Serve the `index.html` file with the arguments of the app injected.
+///
+/// The file is read on every request, so that changes are picked up.
+fn serve_index_file(path: PathBuf, dev_server: &DevServer) -> axum::routing::MethodRouter {
+    let app_args = dev_server.app_args.clone();
+
+    get(async move || match fs::read_to_string(&path) {
+        Ok(index) => (
+            [(http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            app_args.inject(&index),
+        )
+            .into_response(),
+        Err(error) => {
+            tracing::error!("failed to read {}: {error}", path.display());
+            StatusCode::NOT_FOUND.into_response()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_log_message_from_browser() -> anyhow::Result<()> {
+        let msg: BrowserMessage =
+            serde_json::from_str(r#"{"type":"log","level":"warn","message":"low fps"}"#)?;
+
+        assert_eq!(
+            msg,
+            BrowserMessage::Log {
+                level: BrowserLogLevel::Warn,
+                message: "low fps".to_owned()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_serialize_messages_for_browser() -> anyhow::Result<()> {
+        assert_eq!(
+            serde_json::to_string(&DevServerMessage::BuildError {
+                message: "oops".to_owned()
+            })?,
+            r#"{"type":"build-error","message":"oops"}"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,706 @@
+//! Breaking down the size of the Wasm binary by sections, crates and functions.
+//!
+//! The binary is parsed directly, which only requires understanding the section layout and the
+//! `name` custom section. The report is stored in the target directory, so that the next build
+//! can show how the sizes changed.
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail, ensure};
+use comfy_table::{Table, modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::compress::format_size;
+use crate::bin_target::BinTarget;
+
+/// The number of crates and functions to show in the report.
+const TOP_ENTRIES: usize = 20;
+
+/// The name of the crate group for functions that can't be attributed to a crate.
+const UNKNOWN_CRATE: &str = "[unknown]";
+
+/// The size of a single item in the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SizeEntry {
+    name: String,
+    size: u64,
+}
+
+/// The size breakdown of a Wasm binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SizeReport {
+    /// The size of the whole binary.
+    total_size: u64,
+    /// The sizes of all sections, in the order they appear in the binary.
+    sections: Vec<SizeEntry>,
+    /// The code size of the largest crates, in descending order.
+    crates: Vec<SizeEntry>,
+    /// The largest functions, in descending order.
+    functions: Vec<SizeEntry>,
+    /// Whether the binary contains function names.
+    ///
+    /// Without them, functions can't be attributed to crates.
+    has_names: bool,
+}
+
+impl SizeReport {
+    /// Analyze the Wasm binary.
+    pub(crate) fn from_wasm(wasm: &[u8]) -> anyhow::Result<Self> {
+        let module = WasmModule::parse(wasm)?;
+
+        let function_name = |index: u32| {
+            module
+                .function_names
+                .get(&index)
+                .cloned()
+                .unwrap_or_else(|| format!("function[{index}]"))
+        };
+
+        let mut crates: HashMap<String, u64> = HashMap::new();
+        let mut functions = Vec::with_capacity(module.function_sizes.len());
+
+        for &(index, size) in &module.function_sizes {
+            let name = function_name(index);
+            let crate_name = crate_name(&name).unwrap_or(UNKNOWN_CRATE);
+
+            *crates.entry(crate_name.to_owned()).or_default() += size;
+            functions.push(SizeEntry { name, size });
+        }
+
+        Ok(Self {
+            total_size: wasm.len() as u64,
+            sections: module.sections,
+            crates: largest(
+                crates
+                    .into_iter()
+                    .map(|(name, size)| SizeEntry { name, size })
+                    .collect(),
+            ),
+            functions: largest(functions),
+            has_names: !module.function_names.is_empty(),
+        })
+    }
+
+    /// Print the report as tables, showing the difference to the previous report if available.
+    fn print(&self, previous: Option<&Self>) {
+        // The change column is only shown if there is a previous report to compare with
+        let row = |mut cells: Vec<String>, size: u64, previous_size: Option<Option<u64>>| {
+            if previous_size.is_some() {
+                cells.push(format_diff(size, previous_size));
+            }
+            cells
+        };
+        let header = |mut columns: Vec<&'static str>| {
+            if previous.is_some() {
+                columns.push("Change");
+            }
+            report_table(columns)
+        };
+
+        let mut sections = header(vec!["Section", "Size", "Share"]);
+        for section in &self.sections {
+            sections.add_row(row(
+                vec![
+                    section.name.clone(),
+                    format_size(section.size),
+                    format_share(section.size, self.total_size),
+                ],
+                section.size,
+                previous.map(|previous| size_of(&previous.sections, &section.name)),
+            ));
+        }
+        sections.add_row(row(
+            vec![
+                "total".to_owned(),
+                format_size(self.total_size),
+                String::new(),
+            ],
+            self.total_size,
+            previous.map(|previous| Some(previous.total_size)),
+        ));
+        println!("{sections}");
+
+        if !self.has_names {
+            warn!(
+                "the Wasm binary doesn't contain function names, so the code can't be attributed to crates"
+            );
+            warn!(
+                "the names are removed by `wasm-opt --strip-debug` and `strip = true` in the compilation profile, use e.g. `--wasm-opt=-Os` to keep them"
+            );
+            return;
+        }
+
+        let code_size = self.size_of_section("code");
+
+        let mut crates = header(vec!["Crate", "Code size", "Share"]);
+        for krate in &self.crates {
+            crates.add_row(row(
+                vec![
+                    krate.name.clone(),
+                    format_size(krate.size),
+                    format_share(krate.size, code_size),
+                ],
+                krate.size,
+                previous.map(|previous| size_of(&previous.crates, &krate.name)),
+            ));
+        }
+        println!("{crates}");
+
+        let mut functions = header(vec!["Function", "Size"]);
+        for function in &self.functions {
+            functions.add_row(row(
+                vec![function.name.clone(), format_size(function.size)],
+                function.size,
+                previous.map(|previous| size_of(&previous.functions, &function.name)),
+            ));
+        }
+        println!("{functions}");
+    }
+
+    /// The size of the section with the given name, or 0 if it doesn't exist.
+    fn size_of_section(&self, name: &str) -> u64 {
+        size_of(&self.sections, name).unwrap_or_default()
+    }
+}
+
+/// Create the size report for the final Wasm binary of the app and print it.
+///
+/// The report is stored in the target directory and compared to the report of the previous build.
+pub(crate) fn report_wasm_size(
+    target_directory: &Path,
+    profile: &str,
+    bin_target: &BinTarget,
+) -> anyhow::Result<()> {
+    let wasm_path = bin_target
+        .artifact_directory
+        .join(format!("{}_bg.wasm", bin_target.bin_name));
+    let wasm =
+        fs::read(&wasm_path).with_context(|| format!("failed to read {}", wasm_path.display()))?;
+
+    let report = SizeReport::from_wasm(&wasm).context("failed to analyze the Wasm binary")?;
+
+    let report_path = report_path(target_directory, profile, &bin_target.bin_name);
+    // An outdated or corrupted report is simply ignored
+    let previous = fs::read_to_string(&report_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<SizeReport>(&content).ok());
+
+    info!("size report for file://{}:", wasm_path.display());
+    report.print(previous.as_ref());
+
+    if let Some(parent) = report_path.parent() {
+        fs::create_dir_all(parent).context("failed to create size report directory")?;
+    }
+    fs::write(&report_path, serde_json::to_string_pretty(&report)?)
+        .context("failed to store size report")?;
+    tracing::debug!("stored size report at file://{}", report_path.display());
+
+    Ok(())
+}
+
+/// The path where the size report of the last build is stored.
+fn report_path(target_directory: &Path, profile: &str, bin_name: &str) -> PathBuf {
+    target_directory
+        .join("bevy_web")
+        .join("size-reports")
+        .join(profile)
+        .join(format!("{bin_name}.json"))
+}
+
+/// The parts of a Wasm module that are relevant for the size report.
+#[derive(Debug, Default)]
+struct WasmModule {
+    /// The sizes of all sections, including their headers.
+    sections: Vec<SizeEntry>,
+    /// The function index and body size of all functions defined in the module.
+    function_sizes: Vec<(u32, u64)>,
+    /// The function names from the `name` custom section.
+    function_names: HashMap<u32, String>,
+}
+
+impl WasmModule {
+    /// Parse the sections of the Wasm binary.
+    ///
+    /// See <https://webassembly.github.io/spec/core/binary/modules.html>.
+    fn parse(wasm: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = Reader::new(wasm);
+
+        ensure!(reader.bytes(4)? == b"\0asm", "not a Wasm binary");
+        ensure!(
+            reader.bytes(4)? == [1, 0, 0, 0],
+            "unsupported Wasm binary version"
+        );
+
+        let mut module = Self::default();
+        let mut imported_functions = 0;
+
+        while !reader.is_empty() {
+            let start = reader.position;
+            let id = reader.byte()?;
+            let size = reader.leb()? as usize;
+            let mut content = Reader::new(reader.bytes(size)?);
+            let section_size = (reader.position - start) as u64;
+
+            let name = match id {
+                0 => {
+                    let name = content.name()?;
+                    if name == "name" {
+                        module.function_names = parse_function_names(&mut content)
+                            .context("failed to parse the name section")?;
+                    }
+                    format!("custom: {name}")
+                }
+                1 => "type".to_owned(),
+                2 => {
+                    imported_functions = count_imported_functions(&mut content)
+                        .context("failed to parse the import section")?;
+                    "import".to_owned()
+                }
+                3 => "function".to_owned(),
+                4 => "table".to_owned(),
+                5 => "memory".to_owned(),
+                6 => "global".to_owned(),
+                7 => "export".to_owned(),
+                8 => "start".to_owned(),
+                9 => "element".to_owned(),
+                10 => {
+                    module.function_sizes = parse_function_sizes(&mut content, imported_functions)
+                        .context("failed to parse the code section")?;
+                    "code".to_owned()
+                }
+                11 => "data".to_owned(),
+                12 => "data count".to_owned(),
+                13 => "tag".to_owned(),
+                id => format!("unknown ({id})"),
+            };
+
+            module.sections.push(SizeEntry {
+                name,
+                size: section_size,
+            });
+        }
+
+        Ok(module)
+    }
+}
+
+/// Count the imported functions, which come before the defined functions in the index space.
+fn count_imported_functions(reader: &mut Reader) -> anyhow::Result<u32> {
+    let count = reader.leb()?;
+    let mut functions = 0;
+
+    for _ in 0..count {
+        // Module and field name
+        reader.name()?;
+        reader.name()?;
+
+        match reader.byte()? {
+            // Function: type index
+            0x00 => {
+                reader.leb()?;
+                functions += 1;
+            }
+            // Table: reference type and limits
+            0x01 => {
+                reader.byte()?;
+                reader.limits()?;
+            }
+            // Memory: limits
+            0x02 => reader.limits()?,
+            // Global: value type and mutability
+            0x03 => {
+                reader.byte()?;
+                reader.byte()?;
+            }
+            // Tag: attribute and type index
+            0x04 => {
+                reader.byte()?;
+                reader.leb()?;
+            }
+            kind => bail!("unknown import kind {kind:#x}"),
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Determine the body size of every function in the code section.
+fn parse_function_sizes(
+    reader: &mut Reader,
+    imported_functions: u32,
+) -> anyhow::Result<Vec<(u32, u64)>> {
+    let count = reader.leb()?;
+    // Every function takes at least one byte, so a larger count can't be valid
+    let mut sizes = Vec::with_capacity(count.min(reader.remaining() as u64) as usize);
+
+    for index in 0..count {
+        let size = reader.leb()?;
+        reader.bytes(size as usize)?;
+        let index = u32::try_from(index)
+            .ok()
+            .and_then(|index| imported_functions.checked_add(index))
+            .context("too many functions in the Wasm binary")?;
+        sizes.push((index, size));
+    }
+
+    Ok(sizes)
+}
+
+/// Parse the function names subsection of the `name` custom section.
+///
+/// See <https://webassembly.github.io/spec/core/appendix/custom.html#name-section>.
+fn parse_function_names(reader: &mut Reader) -> anyhow::Result<HashMap<u32, String>> {
+    const FUNCTION_NAMES: u8 = 1;
+
+    let mut names = HashMap::new();
+
+    while !reader.is_empty() {
+        let id = reader.byte()?;
+        let size = reader.leb()? as usize;
+        let mut subsection = Reader::new(reader.bytes(size)?);
+
+        if id != FUNCTION_NAMES {
+            continue;
+        }
+
+        let count = subsection.leb()?;
+        for _ in 0..count {
+            let index = subsection.leb()? as u32;
+            let name = subsection.name()?;
+            names.insert(index, demangle(&name));
+        }
+    }
+
+    Ok(names)
+}
+
+/// A cursor over the bytes of a Wasm binary.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    /// The number of bytes that haven't been read yet.
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    fn byte(&mut self) -> anyhow::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .position
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.position..end))
+            .context("unexpected end of the Wasm binary")?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Read an unsigned LEB128 integer.
+    fn leb(&mut self) -> anyhow::Result<u64> {
+        let mut result = 0;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            result |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+
+        bail!("invalid LEB128 integer")
+    }
+
+    /// Read a length-prefixed UTF-8 string.
+    fn name(&mut self) -> anyhow::Result<String> {
+        let len = self.leb()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+
+    /// Read the limits of a table or memory.
+    fn limits(&mut self) -> anyhow::Result<()> {
+        let flags = self.byte()?;
+        self.leb()?;
+        if flags & 0x01 != 0 {
+            self.leb()?;
+        }
+        Ok(())
+    }
+}
+
+/// Demangle legacy Rust symbol names, e.g. `_ZN4core3fmt5write17h0123456789abcdefE`.
+///
+/// Names that are already demangled or use another mangling scheme are returned as-is.
+fn demangle(name: &str) -> String {
+    let Some(mut rest) = name
+        .strip_prefix("_ZN")
+        .and_then(|name| name.strip_suffix('E'))
+    else {
+        return name.to_owned();
+    };
+
+    let mut components = Vec::new();
+
+    while !rest.is_empty() {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let Some(len) = rest[..digits].parse::<usize>().ok() else {
+            return name.to_owned();
+        };
+        let Some(component) = rest.get(digits..digits + len) else {
+            return name.to_owned();
+        };
+
+        components.push(component);
+        rest = &rest[digits + len..];
+    }
+
+    // The last component is a hash, e.g. `h0123456789abcdef`
+    if components
+        .last()
+        .is_some_and(|last| last.len() == 17 && last.starts_with('h'))
+    {
+        components.pop();
+    }
+
+    components
+        .into_iter()
+        .map(|component| {
+            // Components starting with an escape sequence are prefixed with `_`
+            component
+                .strip_prefix("_$")
+                .map_or_else(|| component.to_owned(), |rest| format!("${rest}"))
+                .replace("..", "::")
+                .replace("$LT$", "<")
+                .replace("$GT$", ">")
+                .replace("$u20$", " ")
+                .replace("$RF$", "&")
+                .replace("$BP$", "*")
+                .replace("$LP$", "(")
+                .replace("$RP$", ")")
+                .replace("$C$", ",")
+                .replace("$u7b$", "{")
+                .replace("$u7d$", "}")
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// The name of the crate that defines the function, e.g. `bevy_ecs` for
+/// `<bevy_ecs::world::World as core::default::Default>::default`.
+fn crate_name(function_name: &str) -> Option<&str> {
+    let path = function_name.trim_start_matches(['<', '&', '*']);
+    let path = path.strip_prefix("mut ").unwrap_or(path);
+    let (crate_name, _) = path.split_once("::")?;
+
+    crate_name
+        .chars()
+        .all(|char| char.is_alphanumeric() || char == '_')
+        .then_some(crate_name)
+        .filter(|crate_name| !crate_name.is_empty())
+}
+
+/// Sort the entries by size and keep the largest ones.
+fn largest(mut entries: Vec<SizeEntry>) -> Vec<SizeEntry> {
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    entries.truncate(TOP_ENTRIES);
+    entries
+}
+
+/// The size of the entry with the given name.
+fn size_of(entries: &[SizeEntry], name: &str) -> Option<u64> {
+    entries
+        .iter()
+        .find(|entry| entry.name == name)
+        .map(|entry| entry.size)
+}
+
+/// Create a table in the style of the other CLI output.
+fn report_table(header: Vec<&str>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_header(header);
+    table
+}
+
+/// Format the share of the size in the total, e.g. `12.3%`.
+fn format_share(size: u64, total: u64) -> String {
+    if total == 0 {
+        return String::new();
+    }
+
+    format!("{:.1}%", size as f64 / total as f64 * 100.)
+}
+
+/// Format the difference to the previous size.
+///
+/// `previous` is [`None`] if there is no previous report, and `Some(None)` if the entry is new.
+fn format_diff(size: u64, previous: Option<Option<u64>>) -> String {
+    match previous {
+        None => String::new(),
+        Some(None) => "new".to_owned(),
+        Some(Some(previous)) if previous == size => "-".to_owned(),
+        Some(Some(previous)) if size > previous => format!("+{}", format_size(size - previous)),
+        Some(Some(previous)) => format!("-{}", format_size(previous - size)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode an unsigned LEB128 integer.
+    fn leb(mut value: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    fn name(name: &str) -> Vec<u8> {
+        [leb(name.len()), name.as_bytes().to_vec()].concat()
+    }
+
+    fn section(id: u8, content: Vec<u8>) -> Vec<u8> {
+        [vec![id], leb(content.len()), content].concat()
+    }
+
+    /// A module with one imported and two defined functions.
+    fn test_module() -> Vec<u8> {
+        let import = section(
+            2,
+            [leb(1), name("env"), name("log"), vec![0x00], leb(0)].concat(),
+        );
+        let code = section(
+            10,
+            [leb(2), leb(3), vec![0; 3], leb(200), vec![0; 200]].concat(),
+        );
+        let function_names = [
+            leb(2),
+            leb(1),
+            name("_ZN4core3fmt5write17h0123456789abcdefE"),
+            leb(2),
+            name("<bevy_ecs::world::World as core::default::Default>::default"),
+        ]
+        .concat();
+        let names = section(
+            0,
+            [
+                name("name"),
+                vec![1],
+                leb(function_names.len()),
+                function_names,
+            ]
+            .concat(),
+        );
+
+        [b"\0asm".to_vec(), vec![1, 0, 0, 0], import, code, names].concat()
+    }
+
+    #[test]
+    fn should_report_sections_crates_and_functions() -> anyhow::Result<()> {
+        let wasm = test_module();
+        let report = SizeReport::from_wasm(&wasm)?;
+
+        assert_eq!(report.total_size, wasm.len() as u64);
+        assert_eq!(
+            report
+                .sections
+                .iter()
+                .map(|section| section.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["import", "code", "custom: name"]
+        );
+        assert!(report.has_names);
+        assert_eq!(
+            report.crates,
+            vec![
+                SizeEntry {
+                    name: "bevy_ecs".to_owned(),
+                    size: 200
+                },
+                SizeEntry {
+                    name: "core".to_owned(),
+                    size: 3
+                }
+            ]
+        );
+        assert_eq!(report.functions[1].name, "core::fmt::write");
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_invalid_binaries() {
+        assert!(SizeReport::from_wasm(b"not wasm").is_err());
+        // Truncated section
+        assert!(
+            SizeReport::from_wasm(&[b"\0asm".as_slice(), &[1, 0, 0, 0, 10, 5, 1]].concat())
+                .is_err()
+        );
+        // Huge function count and size
+        let code = section(10, [leb(usize::MAX), leb(usize::MAX)].concat());
+        assert!(
+            SizeReport::from_wasm(&[b"\0asm".to_vec(), vec![1, 0, 0, 0], code].concat()).is_err()
+        );
+    }
+
+    #[test]
+    fn should_demangle_legacy_symbols() {
+        assert_eq!(
+            demangle("_ZN4core3fmt5write17h0123456789abcdefE"),
+            "core::fmt::write"
+        );
+        assert_eq!(
+            demangle(
+                "_ZN59_$LT$bevy_ecs..world..World$u20$as$u20$core..fmt..Debug$GT$3fmt17h0123456789abcdefE"
+            ),
+            "<bevy_ecs::world::World as core::fmt::Debug>::fmt"
+        );
+        assert_eq!(demangle("memcpy"), "memcpy");
+    }
+
+    #[test]
+    fn should_determine_crate_name() {
+        assert_eq!(
+            crate_name("bevy_ecs::world::World::spawn"),
+            Some("bevy_ecs")
+        );
+        assert_eq!(
+            crate_name("<&mut serde_json::de::Deserializer as serde::Deserializer>::parse"),
+            Some("serde_json")
+        );
+        assert_eq!(crate_name("memcpy"), None);
+    }
+
+    #[test]
+    fn should_format_diff() {
+        assert_eq!(format_diff(10, None), "");
+        assert_eq!(format_diff(10, Some(None)), "new");
+        assert_eq!(format_diff(2048, Some(Some(1024))), "+1.00 KiB");
+        assert_eq!(format_diff(10, Some(Some(20))), "-10 B");
+    }
+}
@@ -0,0 +1,187 @@
+//! Rebuilding the app and reloading assets automatically while it is served in the browser.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use cargo_metadata::Metadata;
+use sha2::{Digest as _, Sha256};
+use tracing::{error, info};
+
+use super::{
+    build::build_web,
+    bundle::{LinkedBundle, WebBundle},
+    serve::{DevServer, DevServerMessage},
+};
+use crate::{
+    bin_target::BinTarget,
+    commands::{build::BuildArgs, run::RunArgs},
+    watch::{FileWatcher, watched_source_paths},
+};
+
+/// The files and directories to watch for the given app.
+///
+/// This includes the sources of the package and its local dependencies and the custom web assets.
+/// The Bevy assets are not included, because they can be reloaded without rebuilding the app,
+/// see [`watch_assets`].
+pub(crate) fn watched_paths(
+    metadata: &Metadata,
+    bin_target: &BinTarget,
+    web_bundle: &WebBundle,
+) -> Vec<PathBuf> {
+    let mut paths = watched_source_paths(metadata, bin_target.package);
+
+    if let WebBundle::Linked(LinkedBundle { web_assets, .. }) = web_bundle {
+        paths.extend(web_assets.iter().cloned());
+    }
+
+    paths
+}
+
+/// Rebuild the app whenever one of the watched files changes.
+///
+/// The browser is notified via the dev server, either to reload the page or to display the build
+/// error. This function never returns and is meant to be run in a separate thread.
+pub(crate) fn watch_and_rebuild(
+    args: RunArgs,
+    metadata: Metadata,
+    paths: Vec<PathBuf>,
+    dev_server: DevServer,
+) {
+    let mut watcher = FileWatcher::new(paths);
+
+    tracing::debug!("watching for changes in {:?}", watcher.paths());
+
+    loop {
+        let changes = watcher.wait_for_changes();
+        tracing::debug!("changed files: {changes:?}");
+        info!(
+            "detected changes in {} file(s), rebuilding...",
+            changes.len()
+        );
+
+        // Start from the original arguments,
+        // because building modifies them (e.g. to apply the default profiles)
+        let mut build_args: BuildArgs = args.clone().into();
+
+        match build_web(&mut build_args, &metadata) {
+            Ok(web_bundle) => {
+                dev_server.update_bundle(&web_bundle);
+                dev_server.send(DevServerMessage::Reload);
+                info!("rebuilt the app, reloading the page...");
+            }
+            Err(build_error) => {
+                error!("failed to rebuild the app: {build_error:?}");
+                dev_server.send(DevServerMessage::BuildError {
+                    message: format!("{build_error:?}"),
+                });
+            }
+        }
+    }
+}
+
+/// Notify the browser whenever a file in the assets folder changes.
+///
+/// This allows the app to reload single assets without losing its state.
+/// This function never returns and is meant to be run in a separate thread.
+pub(crate) fn watch_assets(assets_path: PathBuf, dev_server: DevServer) {
+    let mut watcher = FileWatcher::new([assets_path.clone()]);
+
+    loop {
+        for path in watcher.wait_for_changes() {
+            // Removed files can't be reloaded
+            let Some(message) = asset_changed_message(&assets_path, &path) else {
+                continue;
+            };
+
+            if let DevServerMessage::AssetChanged { path, .. } = &message {
+                info!("asset `{path}` changed");
+            }
+
+            dev_server.send(message);
+        }
+    }
+}
+
+/// Create the message to notify the browser about a changed asset.
+///
+/// Returns [`None`] if the asset doesn't exist (anymore).
+fn asset_changed_message(assets_path: &Path, path: &Path) -> Option<DevServerMessage> {
+    let content = fs::read(path).ok()?;
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis() as u64;
+
+    let hash = Sha256::digest(&content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    Some(DevServerMessage::AssetChanged {
+        path: asset_path(assets_path, path)?,
+        modified,
+        hash,
+    })
+}
+
+/// The path of the asset as used by Bevy, relative to the assets folder and separated by `/`.
+fn asset_path(assets_path: &Path, path: &Path) -> Option<String> {
+    let relative_path = path.strip_prefix(assets_path).ok()?;
+
+    Some(
+        relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_determine_asset_path_relative_to_assets_folder() {
+        assert_eq!(
+            asset_path(
+                Path::new("/game/assets"),
+                &Path::new("/game/assets")
+                    .join("textures")
+                    .join("player.png")
+            ),
+            Some("textures/player.png".to_owned())
+        );
+    }
+
+    #[test]
+    fn should_ignore_files_outside_of_assets_folder() {
+        assert_eq!(
+            asset_path(Path::new("/game/assets"), Path::new("/game/src/main.rs")),
+            None
+        );
+    }
+
+    #[test]
+    fn should_hash_asset_content_with_sha256() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("hello.txt"), "hello")?;
+
+        let Some(DevServerMessage::AssetChanged { path, hash, .. }) =
+            asset_changed_message(dir.path(), &dir.path().join("hello.txt"))
+        else {
+            panic!("expected an asset changed message");
+        };
+
+        assert_eq!(path, "hello.txt");
+        assert_eq!(
+            hash,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        Ok(())
+    }
+}